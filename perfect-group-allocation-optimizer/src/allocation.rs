@@ -0,0 +1,155 @@
+use itertools::Itertools;
+
+/// A workshop together with the room it was placed in.
+/// Workshops spanning multiple timeslots are listed once for every timeslot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScheduledWorkshop {
+    pub topic: String,
+    pub timeslot: String,
+    pub room: String,
+}
+
+/// A participant attending a workshop, once for every timeslot the workshop spans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Assignment {
+    pub participant: String,
+    pub topic: String,
+    pub timeslot: String,
+    pub room: String,
+}
+
+/// The result of a successful allocation run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Allocation {
+    pub workshops: Vec<ScheduledWorkshop>,
+    pub assignments: Vec<Assignment>,
+    /// the summed rank of all assignments
+    pub objective: f64,
+    /// how far the objective of the last solved model may be from the optimum relative to it,
+    /// zero if it is proven optimal and `None` if the solver doesn't report it.
+    /// `HiGHS` always reports `None` as `good_lp` doesn't return its gap
+    pub gap: Option<f64>,
+    /// the seed of the lottery between equally good allocations, see [`crate::SolverOptions::lottery`]
+    pub lottery: Option<u64>,
+}
+
+/// A 64 bit FNV-1a hash of the sorted `lines`, unlike [`core::hash::Hash`] it is stable
+/// across platforms and compiler versions.
+pub fn fingerprint(lines: impl Iterator<Item = String>) -> String {
+    let hash = lines
+        .sorted()
+        .flat_map(|line| line.into_bytes().into_iter().chain([b'\n']))
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{hash:016x}")
+}
+
+impl Allocation {
+    /// Identifies the workshops and assignments independent of their order. Solving the same
+    /// problem with the same [`crate::SolverOptions`] always results in the same fingerprint.
+    #[must_use]
+    pub fn fingerprint(&self) -> String {
+        fingerprint(
+            self.workshops
+                .iter()
+                .map(|workshop| {
+                    format!(
+                        "workshop\t{}\t{}\t{}",
+                        workshop.timeslot, workshop.room, workshop.topic
+                    )
+                })
+                .chain(self.assignments.iter().map(|assignment| {
+                    format!(
+                        "assignment\t{}\t{}\t{}\t{}",
+                        assignment.participant,
+                        assignment.timeslot,
+                        assignment.room,
+                        assignment.topic
+                    )
+                })),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use crate::{
+        solve, AllocationProblem, Participant, Preference, Rank, Repetitions, Room, RoomSize,
+        SolverOptions, Timeslot, WorkshopDuration, WorkshopTopic, WorkshopTopicSize,
+    };
+
+    /// four participants that like both topics equally, so many allocations are equally good
+    fn tied_problem() -> AllocationProblem {
+        let participants = ["anna", "ben", "carl", "dora"];
+        AllocationProblem {
+            timeslots: vec![Timeslot {
+                identifier: "morning".to_owned(),
+            }],
+            rooms: ["left", "right"]
+                .map(|identifier| Room {
+                    identifier: identifier.to_owned(),
+                    requirements: vec![],
+                    max_size: RoomSize(2),
+                })
+                .into(),
+            topics: ["x", "y"]
+                .map(|identifier| WorkshopTopic {
+                    identifier: identifier.to_owned(),
+                    requirements: vec![],
+                    max_size: WorkshopTopicSize(2),
+                    repetitions: Some(Repetitions { min: 0, max: 1 }),
+                    duration: WorkshopDuration(1),
+                })
+                .into(),
+            participants: participants
+                .map(|identifier| Participant {
+                    identifier: identifier.to_owned(),
+                    timeslots: None,
+                    attributes: BTreeMap::new(),
+                })
+                .into(),
+            preferences: participants
+                .into_iter()
+                .flat_map(|participant| {
+                    ["x", "y"].map(|topic| Preference {
+                        participant: participant.to_owned(),
+                        topic: topic.to_owned(),
+                        rank: Rank(1),
+                    })
+                })
+                .collect(),
+            ..AllocationProblem::default()
+        }
+    }
+
+    #[test]
+    fn fingerprint_is_independent_of_the_input_order() {
+        let problem = tied_problem();
+        let options = SolverOptions::default();
+        let allocation = solve(&problem, &options).unwrap();
+        assert_eq!(allocation.assignments.len(), 4);
+
+        let mut reordered = allocation.clone();
+        reordered.assignments.reverse();
+        reordered.workshops.reverse();
+        assert_eq!(reordered.fingerprint(), allocation.fingerprint());
+        reordered.assignments[0].room = "elsewhere".to_owned();
+        assert_ne!(reordered.fingerprint(), allocation.fingerprint());
+
+        let mut reversed = problem;
+        reversed.participants.reverse();
+        reversed.preferences.reverse();
+        reversed.rooms.reverse();
+        reversed.topics.reverse();
+        assert_eq!(
+            solve(&reversed, &options).unwrap().fingerprint(),
+            allocation.fingerprint()
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use alloc::collections::BTreeSet;
+
+use good_lp::ResolutionError;
+use tracing::info;
+
+use crate::model::{Enforcement, WorkshopModel};
+use crate::solver::SolverRuns;
+use crate::{AllocationProblem, GroupRelation, OptimizerError};
+
+/// A constraint that can make an [`AllocationProblem`] infeasible.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HardConstraint {
+    /// a room can only be used by one workshop per timeslot
+    RoomOccupancy { timeslot: String, room: String },
+    /// every fixed workshop needs a room
+    FixedWorkshops { timeslot: String, topic: String },
+    /// an automatically scheduled topic is held within its [`crate::Repetitions`]
+    Repetitions { topic: String },
+    /// a participant attends a workshop in every timeslot they are present in,
+    /// see [`crate::FreeSlots::Forbidden`]
+    Attendance {
+        timeslot: String,
+        participant: String,
+    },
+    /// the participants of a [`crate::ParticipantGroup`] without a penalty are allocated
+    /// according to its relation
+    Group {
+        participants: Vec<String>,
+        relation: GroupRelation,
+    },
+    /// the participants of the [`GroupRelation::Together`] groups without a penalty
+    /// that attend the workshops of a topic starting in a timeslot fit into the room they share
+    Seating { timeslot: String, topic: String },
+    /// a [`crate::Quota`] without a penalty for one value of its attribute
+    /// and the workshops of a topic starting in a timeslot
+    Quota {
+        target: String,
+        timeslot: Option<String>,
+        attribute: String,
+        value: String,
+    },
+}
+
+impl HardConstraint {
+    #[must_use]
+    pub fn timeslot(&self) -> Option<&str> {
+        match self {
+            Self::RoomOccupancy { timeslot, .. }
+            | Self::FixedWorkshops { timeslot, .. }
+            | Self::Seating { timeslot, .. }
+            | Self::Attendance { timeslot, .. } => Some(timeslot),
+            Self::Quota { timeslot, .. } => timeslot.as_deref(),
+            Self::Repetitions { .. } | Self::Group { .. } => None,
+        }
+    }
+}
+
+/// Explains why an [`AllocationProblem`] has no solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfeasibilityReport {
+    /// timeslots in which more workshops need to be held than there are suitable rooms,
+    /// too few seats in the held workshops show up as [`Self::unseated_participants`] instead
+    pub timeslots_short_of_rooms: Vec<String>,
+    /// participants that can't attend a workshop in one of the timeslots they are present in,
+    /// only known if [`crate::FreeSlots::Forbidden`] as otherwise they just stay without a workshop
+    pub unseated_participants: Vec<String>,
+    /// the constraints that are violated by the least violating relaxation of the problem
+    pub violations: Vec<HardConstraint>,
+    /// a minimal set of constraints that can't be fulfilled together,
+    /// removing any one of them makes the rest feasible
+    pub conflicting: Vec<HardConstraint>,
+}
+
+fn is_feasible(
+    problem: &AllocationProblem,
+    enforced: &BTreeSet<HardConstraint>,
+    runs: &SolverRuns,
+) -> Result<bool, OptimizerError> {
+    match WorkshopModel::new(problem, &Enforcement::Only(enforced))?.solve(runs) {
+        Ok(_) => Ok(true),
+        Err(OptimizerError::Solver(ResolutionError::Infeasible)) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Relaxes the [`HardConstraint`]s of an infeasible problem with slack variables
+/// and searches a minimal conflicting subset of the violated ones using a deletion filter.
+pub fn diagnose(
+    problem: &AllocationProblem,
+    runs: &SolverRuns,
+) -> Result<InfeasibilityReport, OptimizerError> {
+    let elastic = WorkshopModel::new(problem, &Enforcement::Elastic)?.solve(runs)?;
+    let violations = elastic.violated_constraints();
+    info!("violated constraints {violations:?}");
+
+    // only missing rooms count, not e.g. participants that can't attend any workshop
+    let timeslots_short_of_rooms: BTreeSet<&str> = violations
+        .iter()
+        .filter(|violation| {
+            matches!(
+                violation,
+                HardConstraint::RoomOccupancy { .. } | HardConstraint::FixedWorkshops { .. }
+            )
+        })
+        .filter_map(HardConstraint::timeslot)
+        .collect();
+    let timeslots_short_of_rooms = timeslots_short_of_rooms
+        .into_iter()
+        .map(ToOwned::to_owned)
+        .collect();
+
+    let unseated_participants: BTreeSet<&str> = violations
+        .iter()
+        .filter_map(|violation| match violation {
+            HardConstraint::Attendance { participant, .. } => Some(participant.as_str()),
+            _ => None,
+        })
+        .collect();
+    let unseated_participants = unseated_participants
+        .into_iter()
+        .map(ToOwned::to_owned)
+        .collect();
+
+    // the violated constraints usually conflict already, otherwise the relaxation chose them
+    // over other ones and constraints are added until they conflict
+    let mut conflicting: BTreeSet<HardConstraint> = violations.iter().cloned().collect();
+    if is_feasible(problem, &conflicting, runs)? {
+        for hard_constraint in elastic.hard_constraints() {
+            if conflicting.insert(hard_constraint) && !is_feasible(problem, &conflicting, runs)? {
+                break;
+            }
+        }
+    }
+    for hard_constraint in conflicting.clone() {
+        conflicting.remove(&hard_constraint);
+        if is_feasible(problem, &conflicting, runs)? {
+            conflicting.insert(hard_constraint);
+        }
+    }
+
+    Ok(InfeasibilityReport {
+        timeslots_short_of_rooms,
+        unseated_participants,
+        violations,
+        conflicting: conflicting.into_iter().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use crate::{
+        solve, AllocationProblem, FreeSlots, HardConstraint, OptimizerError, Participant,
+        Preference, Rank, Room, RoomSize, SolverOptions, Timeslot, Workshop, WorkshopDuration,
+        WorkshopTopic, WorkshopTopicSize,
+    };
+
+    /// two participants that both have to attend the only workshop, which has one seat
+    fn too_few_seats() -> AllocationProblem {
+        AllocationProblem {
+            timeslots: vec![Timeslot {
+                identifier: "morning".to_owned(),
+            }],
+            rooms: vec![Room {
+                identifier: "room".to_owned(),
+                requirements: vec![],
+                max_size: RoomSize(1),
+            }],
+            topics: vec![WorkshopTopic {
+                identifier: "topic".to_owned(),
+                requirements: vec![],
+                max_size: WorkshopTopicSize(10),
+                repetitions: None,
+                duration: WorkshopDuration(1),
+            }],
+            workshops: vec![Workshop {
+                topic: "topic".to_owned(),
+                timeslot: "morning".to_owned(),
+            }],
+            participants: ["alice", "bob"]
+                .map(|identifier| Participant {
+                    identifier: identifier.to_owned(),
+                    timeslots: None,
+                    attributes: BTreeMap::new(),
+                })
+                .into(),
+            preferences: ["alice", "bob"]
+                .map(|participant| Preference {
+                    participant: participant.to_owned(),
+                    topic: "topic".to_owned(),
+                    rank: Rank(1),
+                })
+                .into(),
+            free_slots: FreeSlots::Forbidden,
+            ..AllocationProblem::default()
+        }
+    }
+
+    #[test]
+    fn reports_participants_without_seat() {
+        let Err(OptimizerError::Infeasible(report)) =
+            solve(&too_few_seats(), &SolverOptions::default())
+        else {
+            panic!("problem should be infeasible");
+        };
+        assert!(report.timeslots_short_of_rooms.is_empty());
+        assert_eq!(report.unseated_participants.len(), 1);
+        let attendance = |participant: &str| HardConstraint::Attendance {
+            timeslot: "morning".to_owned(),
+            participant: participant.to_owned(),
+        };
+        assert_eq!(
+            report.conflicting,
+            vec![attendance("alice"), attendance("bob")]
+        );
+    }
+
+    #[test]
+    fn reports_timeslots_short_of_rooms() {
+        let mut problem = too_few_seats();
+        problem.topics.push(WorkshopTopic {
+            identifier: "other".to_owned(),
+            ..problem.topics[0].clone()
+        });
+        problem.workshops.push(Workshop {
+            topic: "other".to_owned(),
+            timeslot: "morning".to_owned(),
+        });
+        problem.rooms[0].max_size = RoomSize(2);
+        let Err(OptimizerError::Infeasible(report)) = solve(&problem, &SolverOptions::default())
+        else {
+            panic!("problem should be infeasible");
+        };
+        assert_eq!(report.timeslots_short_of_rooms, vec!["morning".to_owned()]);
+    }
+}
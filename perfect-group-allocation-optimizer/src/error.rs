@@ -0,0 +1,60 @@
+use good_lp::ResolutionError;
+use thiserror::Error;
+
+use crate::InfeasibilityReport;
+
+#[derive(Error, Debug)]
+pub enum OptimizerError {
+    #[error("identifier `{0}` is used multiple times")]
+    DuplicateIdentifier(String),
+    #[error("unknown requirement `{0}`")]
+    UnknownRequirement(String),
+    #[error("unknown timeslot `{0}`")]
+    UnknownTimeslot(String),
+    #[error("unknown room `{0}`")]
+    UnknownRoom(String),
+    #[error("unknown workshop topic `{0}`")]
+    UnknownTopic(String),
+    #[error("unknown participant `{0}`")]
+    UnknownParticipant(String),
+    #[error("unknown project `{0}`")]
+    UnknownProject(String),
+    #[error("participants {0:?} have no preferred or fallback project they are eligible for")]
+    UnassignableParticipants(Vec<String>),
+    #[error("no room fulfills the requirements of workshop topics {0:?}")]
+    UnmatchedRequirements(Vec<String>),
+    #[error("workshop topic `{0}` is scheduled automatically but also has fixed workshops")]
+    ConflictingSchedule(String),
+    #[error("workshops of topic `{0}` don't fit into the timeslots after their start")]
+    InvalidDuration(String),
+    #[error("allocation problem is infeasible, conflicting constraints {:?}", .0.conflicting)]
+    Infeasible(InfeasibilityReport),
+    #[error("unknown or disabled solver backend `{0}`")]
+    UnknownBackend(String),
+    #[error("solver failed {0}")]
+    Solver(#[from] ResolutionError),
+    #[error("heuristic could not place all workshops that need to be held")]
+    HeuristicFailed,
+    #[error("solver found no allocation within the time limit")]
+    TimeLimitReached,
+    #[error("relative MIP gap {0} is not a non-negative number")]
+    InvalidMipGap(f64),
+    #[error("solver seed has to be positive")]
+    InvalidSeed,
+    #[error("solution file contains no values of model variables")]
+    InvalidSolution,
+    #[error("failed to read or write model {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// An error while reading or writing the files of [`crate::files`].
+#[cfg(feature = "cli")]
+#[derive(Error, Debug)]
+pub enum FileError {
+    #[error("failed to access file {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid JSON {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid CSV {0}")]
+    Csv(#[from] csv::Error),
+}
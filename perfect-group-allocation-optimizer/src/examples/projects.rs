@@ -1 +1,85 @@
+use alloc::collections::BTreeMap;
 
+use crate::projects::{Fallback, Project, ProjectParticipant, ProjectPreference, ProjectProblem};
+use crate::{GroupRelation, Objective, ParticipantGroup, Quota, QuotaLimit, Rank};
+
+#[must_use]
+pub fn example() -> ProjectProblem {
+    ProjectProblem {
+        projects: vec![
+            Project {
+                identifier: "theater".to_owned(),
+                min_age: 10,
+                max_age: 18,
+                min_participants: 2,
+                max_participants: 20,
+                leaders: vec![],
+                random_assignments: true,
+            },
+            Project {
+                identifier: "fussball".to_owned(),
+                min_age: 12,
+                max_age: 18,
+                min_participants: 1,
+                max_participants: 22,
+                leaders: vec!["moritz".to_owned()],
+                random_assignments: false,
+            },
+        ],
+        participants: vec![
+            ProjectParticipant {
+                identifier: "moritz".to_owned(),
+                age: 16,
+                attributes: BTreeMap::from([("class".to_owned(), "10a".to_owned())]),
+            },
+            ProjectParticipant {
+                identifier: "anna".to_owned(),
+                age: 11,
+                attributes: BTreeMap::from([("class".to_owned(), "6b".to_owned())]),
+            },
+            ProjectParticipant {
+                identifier: "lena".to_owned(),
+                age: 13,
+                attributes: BTreeMap::from([("class".to_owned(), "6b".to_owned())]),
+            },
+        ],
+        preferences: vec![
+            ProjectPreference {
+                participant: "moritz".to_owned(),
+                project: "fussball".to_owned(),
+                rank: Rank(2),
+            },
+            ProjectPreference {
+                participant: "moritz".to_owned(),
+                project: "theater".to_owned(),
+                rank: Rank(1),
+            },
+            ProjectPreference {
+                participant: "anna".to_owned(),
+                project: "fussball".to_owned(),
+                rank: Rank(2),
+            },
+            ProjectPreference {
+                participant: "anna".to_owned(),
+                project: "theater".to_owned(),
+                rank: Rank(1),
+            },
+        ],
+        groups: vec![ParticipantGroup {
+            participants: vec!["moritz".to_owned(), "anna".to_owned()],
+            relation: GroupRelation::Together,
+            penalty: Some(1),
+        }],
+        // the projects should mix the classes
+        quotas: vec![Quota {
+            targets: vec![],
+            attribute: "class".to_owned(),
+            value: None,
+            min: None,
+            max: Some(QuotaLimit::Percent(60)),
+            penalty: Some(1),
+        }],
+        objective: Objective::Lexicographic,
+        fallback: Fallback::LeastFull,
+    }
+}
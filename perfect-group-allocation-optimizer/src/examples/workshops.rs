@@ -1,61 +1,121 @@
-// these will come from the database later
-// it needs to be ensured that the names are *globally* unique, so it makes sense to append an id there
+use alloc::collections::BTreeMap;
 
-#[derive(Debug)]
-pub struct Requirement {
-    pub identifier: String,
-}
-
-#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Timeslot {
-    pub identifier: String,
-}
-
-#[derive(Debug)]
-pub struct RoomSize(pub u8);
-
-#[derive(Debug)]
-pub struct Room<'a> {
-    pub identifier: String,
-    pub requirements: Vec<&'a Requirement>,
-    pub max_size: RoomSize,
-}
-
-/// Not every room may be available in every timeslot
-#[derive(Debug)]
-pub struct RoomInTimeSlot<'a> {
-    pub room: &'a Room<'a>,
-    pub timeslot: &'a Timeslot,
-}
-
-#[derive(Debug)]
-pub struct WorkshopTopicSize(pub u8);
-
-#[derive(Debug)]
-pub struct WorkshopTopic<'a> {
-    pub identifier: String,
-    pub requirements: Vec<&'a Requirement>,
-    pub max_size: WorkshopTopicSize,
-}
-
-// in theory if a person holds multiple workshops the system could decide which one should be held how many times. but we probably leave that problem for now.
-#[derive(Debug)]
-pub struct Workshop<'a> {
-    pub topic: &'a WorkshopTopic<'a>,
-    pub timeslot: &'a Timeslot,
-}
-
-#[derive(Debug)]
-pub struct Participant {
-    pub identifier: String,
-}
-
-#[derive(Debug)]
-pub struct Rank(pub u8);
+use crate::{
+    AllocationProblem, FreeSlots, GroupRelation, Objective, Participant, ParticipantGroup,
+    Preference, Rank, Repetitions, Requirement, Room, RoomAvailability, RoomSize, Timeslot,
+    Workshop, WorkshopDuration, WorkshopTopic, WorkshopTopicSize,
+};
 
-#[derive(Debug)]
-pub struct Preference<'a> {
-    pub participant: &'a Participant,
-    pub topic: &'a WorkshopTopic<'a>,
-    pub rank: Rank,
+#[must_use]
+pub fn example() -> AllocationProblem {
+    AllocationProblem {
+        requirements: vec![
+            Requirement {
+                identifier: "Outside".to_owned(),
+            },
+            Requirement {
+                identifier: "Computer-Pool".to_owned(),
+            },
+        ],
+        timeslots: vec![
+            Timeslot {
+                identifier: "morgens".to_owned(),
+            },
+            Timeslot {
+                identifier: "mittags".to_owned(),
+            },
+            Timeslot {
+                identifier: "abends".to_owned(),
+            },
+        ],
+        rooms: vec![
+            Room {
+                identifier: "C-Pool".to_owned(),
+                requirements: vec!["Computer-Pool".to_owned()],
+                max_size: RoomSize(128),
+            },
+            Room {
+                identifier: "Bosch".to_owned(),
+                requirements: vec![],
+                max_size: RoomSize(75),
+            },
+            Room {
+                identifier: "draussen".to_owned(),
+                requirements: vec!["Outside".to_owned()],
+                max_size: RoomSize(200),
+            },
+        ],
+        // outside is only possible in the afternoon
+        room_availability: vec![
+            RoomAvailability {
+                room: "draussen".to_owned(),
+                timeslot: "mittags".to_owned(),
+                max_size: None,
+            },
+            RoomAvailability {
+                room: "draussen".to_owned(),
+                timeslot: "abends".to_owned(),
+                max_size: Some(RoomSize(100)),
+            },
+        ],
+        topics: vec![
+            WorkshopTopic {
+                identifier: "linux-lernen".to_owned(),
+                requirements: vec!["Computer-Pool".to_owned()],
+                max_size: WorkshopTopicSize(50),
+                repetitions: None,
+                duration: WorkshopDuration(1),
+            },
+            // an excursion taking the whole afternoon
+            WorkshopTopic {
+                identifier: "wanderung".to_owned(),
+                requirements: vec!["Outside".to_owned()],
+                max_size: WorkshopTopicSize(30),
+                repetitions: Some(Repetitions { min: 0, max: 1 }),
+                duration: WorkshopDuration(2),
+            },
+        ],
+        workshops: vec![Workshop {
+            topic: "linux-lernen".to_owned(),
+            timeslot: "morgens".to_owned(),
+        }],
+        participants: vec![
+            Participant {
+                identifier: "moritz".to_owned(),
+                timeslots: None,
+                attributes: BTreeMap::new(),
+            },
+            // leaves before the evening
+            Participant {
+                identifier: "anna".to_owned(),
+                timeslots: Some(vec!["morgens".to_owned(), "mittags".to_owned()]),
+                attributes: BTreeMap::new(),
+            },
+        ],
+        preferences: vec![
+            Preference {
+                participant: "moritz".to_owned(),
+                topic: "linux-lernen".to_owned(),
+                rank: Rank(2),
+            },
+            Preference {
+                participant: "anna".to_owned(),
+                topic: "linux-lernen".to_owned(),
+                rank: Rank(1),
+            },
+            Preference {
+                participant: "moritz".to_owned(),
+                topic: "wanderung".to_owned(),
+                rank: Rank(1),
+            },
+        ],
+        groups: vec![ParticipantGroup {
+            participants: vec!["moritz".to_owned(), "anna".to_owned()],
+            relation: GroupRelation::Together,
+            penalty: Some(1),
+        }],
+        quotas: vec![],
+        objective: Objective::Utilitarian,
+        free_slots: FreeSlots::Allowed,
+    }
 }
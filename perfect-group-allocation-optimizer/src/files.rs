@@ -0,0 +1,243 @@
+//! Reading [`AllocationProblem`]s from and writing [`Allocation`]s to files.
+//!
+//! A problem is either a single JSON file or a directory of CSV files:
+//!
+//! - `participants.csv` with the column `identifier` and optionally `timeslots`,
+//!   the timeslots the participant is present in are left empty if it is present in all of them
+//! - `timeslots.csv` with the column `identifier`, in chronological order
+//! - `rooms.csv` with the columns `identifier`, `requirements` and `max_size`
+//! - optionally `room_availability.csv` with the columns `room`, `timeslot` and `max_size`,
+//!   the size is left empty if it doesn't differ
+//! - `topics.csv` with the columns `identifier`, `requirements`, `max_size`,
+//!   `min_repetitions` and `max_repetitions`, the repetitions are left empty for fixed workshops,
+//!   and optionally `duration` in timeslots
+//! - `preferences.csv` with the columns `participant`, `topic` and `rank`
+//! - optionally `workshops.csv` with the columns `topic` and `timeslot`
+//!
+//! Requirements and timeslots are separated by `;`. Groups, quotas, participant attributes,
+//! the objective and whether free slots are allowed can only be specified in JSON.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::{
+    Allocation, AllocationProblem, AllocationReport, FileError, Participant, Preference,
+    Repetitions, Requirement, Room, RoomSize, Timeslot, Workshop, WorkshopDuration, WorkshopTopic,
+    WorkshopTopicSize,
+};
+
+#[derive(Deserialize)]
+struct ParticipantRow {
+    identifier: String,
+    timeslots: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RoomRow {
+    identifier: String,
+    requirements: String,
+    max_size: u8,
+}
+
+#[derive(Deserialize)]
+struct TopicRow {
+    identifier: String,
+    requirements: String,
+    max_size: u8,
+    min_repetitions: Option<u8>,
+    max_repetitions: Option<u8>,
+    duration: Option<u8>,
+}
+
+fn split_identifiers(identifiers: &str) -> Vec<String> {
+    identifiers
+        .split(';')
+        .map(str::trim)
+        .filter(|identifier| !identifier.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+fn read_csv<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, FileError> {
+    Ok(csv::Reader::from_path(path)?
+        .deserialize()
+        .collect::<Result<_, _>>()?)
+}
+
+/// Like [`read_csv`] but without any rows if the file `name` doesn't exist in `directory`.
+fn read_optional_csv<T: DeserializeOwned>(
+    directory: &Path,
+    name: &str,
+) -> Result<Vec<T>, FileError> {
+    let path = directory.join(name);
+    if path.exists() {
+        read_csv(&path)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+fn read_csv_directory(directory: &Path) -> Result<AllocationProblem, FileError> {
+    let rooms: Vec<Room> = read_csv::<RoomRow>(&directory.join("rooms.csv"))?
+        .into_iter()
+        .map(|row| Room {
+            identifier: row.identifier,
+            requirements: split_identifiers(&row.requirements),
+            max_size: RoomSize(row.max_size),
+        })
+        .collect();
+    let topics: Vec<WorkshopTopic> = read_csv::<TopicRow>(&directory.join("topics.csv"))?
+        .into_iter()
+        .map(|row| WorkshopTopic {
+            identifier: row.identifier,
+            requirements: split_identifiers(&row.requirements),
+            max_size: WorkshopTopicSize(row.max_size),
+            repetitions: match (row.min_repetitions, row.max_repetitions) {
+                (None, None) => None,
+                (min, max) => Some(Repetitions {
+                    min: min.unwrap_or(0),
+                    max: max.unwrap_or(u8::MAX),
+                }),
+            },
+            duration: row.duration.map(WorkshopDuration).unwrap_or_default(),
+        })
+        .collect();
+    let workshops: Vec<Workshop> = read_optional_csv(directory, "workshops.csv")?;
+
+    // every requirement that is mentioned somewhere
+    let requirements: BTreeSet<&String> = rooms
+        .iter()
+        .flat_map(|room| &room.requirements)
+        .chain(topics.iter().flat_map(|topic| &topic.requirements))
+        .collect();
+
+    Ok(AllocationProblem {
+        requirements: requirements
+            .into_iter()
+            .map(|identifier| Requirement {
+                identifier: identifier.clone(),
+            })
+            .collect(),
+        timeslots: read_csv::<Timeslot>(&directory.join("timeslots.csv"))?,
+        participants: read_csv::<ParticipantRow>(&directory.join("participants.csv"))?
+            .into_iter()
+            .map(|row| Participant {
+                identifier: row.identifier,
+                timeslots: row.timeslots.as_deref().map(split_identifiers),
+                attributes: BTreeMap::new(),
+            })
+            .collect(),
+        preferences: read_csv::<Preference>(&directory.join("preferences.csv"))?,
+        rooms,
+        room_availability: read_optional_csv(directory, "room_availability.csv")?,
+        topics,
+        workshops,
+        ..AllocationProblem::default()
+    })
+}
+
+/// Reads a problem from a JSON file or a directory of CSV files.
+pub fn read_problem(path: &Path) -> Result<AllocationProblem, FileError> {
+    if path.is_dir() {
+        read_csv_directory(path)
+    } else {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+}
+
+/// Reads an allocation written by [`write_allocation_json`].
+pub fn read_allocation(path: &Path) -> Result<Allocation, FileError> {
+    Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+}
+
+pub fn write_problem_json(
+    problem: &AllocationProblem,
+    writer: impl Write,
+) -> Result<(), FileError> {
+    Ok(serde_json::to_writer_pretty(writer, problem)?)
+}
+
+pub fn write_allocation_json(allocation: &Allocation, writer: impl Write) -> Result<(), FileError> {
+    Ok(serde_json::to_writer_pretty(writer, allocation)?)
+}
+
+pub fn write_report_json(report: &AllocationReport, writer: impl Write) -> Result<(), FileError> {
+    Ok(serde_json::to_writer_pretty(writer, report)?)
+}
+
+/// Writes the assignments of `allocation` with the columns `participant`, `topic`, `timeslot` and `room`.
+pub fn write_assignments_csv(allocation: &Allocation, writer: impl Write) -> Result<(), FileError> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for assignment in &allocation.assignments {
+        writer.serialize(assignment)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{read_allocation, read_problem, write_allocation_json, write_problem_json};
+    use crate::{solve, AllocationProblem, RoomSize, SolverOptions};
+
+    #[test]
+    fn round_trips_csv_problem() {
+        let directory = std::env::temp_dir().join(format!("pga-csv-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        for (name, contents) in [
+            ("participants.csv", "identifier,timeslots\nanna,\nben,morning\n"),
+            ("timeslots.csv", "identifier\nmorning\nafternoon\n"),
+            (
+                "rooms.csv",
+                "identifier,requirements,max_size\nlab,computers;sink,10\nhall,,20\n",
+            ),
+            (
+                "room_availability.csv",
+                "room,timeslot,max_size\nlab,morning,5\nhall,afternoon,\n",
+            ),
+            (
+                "topics.csv",
+                "identifier,requirements,max_size,min_repetitions,max_repetitions,duration\n\
+                 coding,computers,10,1,2,\nsports,,20,,,\n",
+            ),
+            (
+                "preferences.csv",
+                "participant,topic,rank\nanna,coding,2\nanna,sports,1\nben,coding,1\nben,sports,2\n",
+            ),
+            ("workshops.csv", "topic,timeslot\nsports,afternoon\n"),
+        ] {
+            fs::write(directory.join(name), contents).unwrap();
+        }
+
+        let problem = read_problem(&directory).unwrap();
+        assert_eq!(problem.requirements.len(), 2);
+        assert_eq!(problem.participants[0].timeslots, None);
+        assert_eq!(
+            problem.participants[1].timeslots,
+            Some(vec!["morning".to_owned()])
+        );
+        assert_eq!(problem.room_availability[0].max_size, Some(RoomSize(5)));
+        assert_eq!(problem.room_availability[1].max_size, None);
+        assert!(problem.topics[0].repetitions.is_some());
+        assert!(problem.topics[1].repetitions.is_none());
+
+        let mut json = Vec::new();
+        write_problem_json(&problem, &mut json).unwrap();
+        let read: AllocationProblem = serde_json::from_slice(&json).unwrap();
+        assert_eq!(read, problem);
+
+        let allocation = solve(&problem, &SolverOptions::default()).unwrap();
+        let path = directory.join("allocation.json");
+        write_allocation_json(&allocation, fs::File::create(&path).unwrap()).unwrap();
+        assert_eq!(read_allocation(&path).unwrap(), allocation);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
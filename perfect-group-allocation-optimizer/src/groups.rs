@@ -0,0 +1,332 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use good_lp::{Expression, Variable};
+use itertools::Itertools;
+
+use crate::diagnosis::HardConstraint;
+use crate::model::HardConstraints;
+use crate::program::{leq, Domain, LinearProgram, Sense};
+use crate::OptimizerError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GroupRelation {
+    /// all participants of the group are allocated to the same project or workshop
+    Together,
+    /// no two participants of the group are allocated to the same project or workshop
+    Apart,
+}
+
+/// Restricts how a group of participants (e.g. a pair of friends) is allocated.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParticipantGroup {
+    /// identifiers of the participants
+    pub participants: Vec<String>,
+    pub relation: GroupRelation,
+    /// `None` makes this a hard constraint, otherwise violating it reduces the summed rank by this amount
+    pub penalty: Option<u16>,
+}
+
+/// Adds the constraints of `groups` to a model, the hard ones as [`HardConstraint::Group`].
+/// Every bucket contains the assignment variables of the participants that are allocated
+/// to one project or workshop.
+pub fn constrain_groups(
+    groups: &[ParticipantGroup],
+    is_participant: impl Fn(&str) -> bool,
+    buckets: &[BTreeMap<&str, Variable>],
+    program: &mut LinearProgram,
+    hard_constraints: &mut HardConstraints,
+) -> Result<(), OptimizerError> {
+    // the model must not depend on the order of the input
+    for group in groups.iter().sorted() {
+        if let Some(participant) = group
+            .participants
+            .iter()
+            .find(|participant| !is_participant(participant))
+        {
+            return Err(OptimizerError::UnknownParticipant(participant.clone()));
+        }
+        let Some((first, others)) = group.participants.split_first() else {
+            continue;
+        };
+
+        // allowed to be one if the group is violated, only exists for soft constraints
+        let violated = group.penalty.map(|penalty| {
+            let violated = program.add_variable(
+                match group.relation {
+                    GroupRelation::Together => "together_",
+                    GroupRelation::Apart => "apart_",
+                }
+                .to_owned()
+                    + &group.participants.join("_"),
+                Domain::Binary,
+            );
+            program.objective -= f64::from(penalty) * violated;
+            violated
+        });
+        let hard_constraint = HardConstraint::Group {
+            participants: group.participants.clone(),
+            relation: group.relation,
+        };
+
+        for bucket in buckets {
+            let assignment = |participant: &String| -> Expression {
+                bucket
+                    .get(participant.as_str())
+                    .map_or_else(Expression::default, |assignment| (*assignment).into())
+            };
+            match group.relation {
+                GroupRelation::Together => {
+                    for other in others {
+                        let difference = assignment(other) - assignment(first);
+                        if let Some(violated) = violated {
+                            program.add_constraint(leq(difference.clone(), violated));
+                            program.add_constraint(leq(-difference, violated));
+                        } else {
+                            hard_constraints.add(
+                                program,
+                                hard_constraint.clone(),
+                                difference,
+                                Sense::Equal,
+                                0.0,
+                            );
+                        }
+                    }
+                }
+                GroupRelation::Apart => {
+                    let together: Expression = group.participants.iter().map(assignment).sum();
+                    if let Some(violated) = violated {
+                        let allowed =
+                            f64::from(u32::try_from(others.len()).unwrap_or(u32::MAX)) * violated;
+                        program.add_constraint(leq(together, 1 + allowed));
+                    } else {
+                        hard_constraints.add(
+                            program,
+                            hard_constraint.clone(),
+                            together,
+                            Sense::LessOrEqual,
+                            1.0,
+                        );
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Distributes the `participants` attending the workshops of a topic starting in the same timeslot
+/// over the `rooms` of these workshops with their number of seats. The attending members of a
+/// [`GroupRelation::Together`] group are seated in the same room unless they don't fit into any room,
+/// participants without a seat left are skipped.
+pub fn seat_participants<'p, R: Copy>(
+    participants: impl IntoIterator<Item = &'p str>,
+    groups: &[ParticipantGroup],
+    rooms: &[(R, usize)],
+) -> Vec<(&'p str, R)> {
+    let mut units = together_units(
+        participants,
+        groups
+            .iter()
+            .filter(|group| group.relation == GroupRelation::Together),
+    );
+    // first fit decreasing, the larger units are seated first
+    units.sort_by_key(|unit| core::cmp::Reverse(unit.len()));
+
+    let mut free_seats: Vec<usize> = rooms.iter().map(|(_, seats)| *seats).collect();
+    let mut seated = Vec::new();
+    for unit in units {
+        let mut room = free_seats
+            .iter()
+            .position(|free| *free >= unit.len())
+            .or_else(|| free_seats.iter().position(|free| *free > 0));
+        for participant in unit {
+            // a unit that doesn't fit into any room is split
+            if room.is_some_and(|room| free_seats[room] == 0) {
+                room = free_seats.iter().position(|free| *free > 0);
+            }
+            let Some(index) = room else {
+                break;
+            };
+            free_seats[index] -= 1;
+            seated.push((participant, rooms[index].0));
+        }
+    }
+    seated.sort_by_key(|(participant, _)| *participant);
+    seated
+}
+
+/// Merges the `participants` that are members of overlapping `groups` into units,
+/// every participant that isn't a member of any group is a unit on its own.
+/// The units are ordered by their first member.
+pub fn together_units<'p, 'g>(
+    participants: impl IntoIterator<Item = &'p str>,
+    groups: impl IntoIterator<Item = &'g ParticipantGroup>,
+) -> Vec<BTreeSet<&'p str>> {
+    let mut units: Vec<BTreeSet<&str>> = participants
+        .into_iter()
+        .map(|participant| BTreeSet::from([participant]))
+        .collect();
+    for group in groups {
+        let (mut merged, others): (Vec<BTreeSet<&str>>, _) = units.into_iter().partition(|unit| {
+            group
+                .participants
+                .iter()
+                .any(|participant| unit.contains(participant.as_str()))
+        });
+        units = others;
+        if let Some(first) = merged.pop() {
+            units.push(merged.into_iter().fold(first, |mut first, unit| {
+                first.extend(unit);
+                first
+            }));
+        }
+    }
+    units.sort();
+    units
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::{BTreeMap, BTreeSet};
+
+    use crate::{
+        solve, AllocationProblem, FreeSlots, GroupRelation, HardConstraint, OptimizerError,
+        Participant, ParticipantGroup, Preference, Rank, Repetitions, Room, RoomSize,
+        SolverOptions, Timeslot, WorkshopDuration, WorkshopTopic, WorkshopTopicSize,
+    };
+
+    /// one topic held in `rooms` rooms with three seats each that all `participants` prefer
+    fn problem(
+        rooms: usize,
+        participants: &[&str],
+        groups: Vec<ParticipantGroup>,
+    ) -> AllocationProblem {
+        AllocationProblem {
+            timeslots: vec![Timeslot {
+                identifier: "morning".to_owned(),
+            }],
+            rooms: (0..rooms)
+                .map(|room| Room {
+                    identifier: format!("room{room}"),
+                    requirements: vec![],
+                    max_size: RoomSize(3),
+                })
+                .collect(),
+            topics: vec![WorkshopTopic {
+                identifier: "topic".to_owned(),
+                requirements: vec![],
+                max_size: WorkshopTopicSize(3),
+                repetitions: Some(Repetitions {
+                    min: 0,
+                    max: u8::try_from(rooms).unwrap(),
+                }),
+                duration: WorkshopDuration(1),
+            }],
+            participants: participants
+                .iter()
+                .map(|identifier| Participant {
+                    identifier: (*identifier).to_owned(),
+                    timeslots: None,
+                    attributes: BTreeMap::new(),
+                })
+                .collect(),
+            preferences: participants
+                .iter()
+                .map(|participant| Preference {
+                    participant: (*participant).to_owned(),
+                    topic: "topic".to_owned(),
+                    rank: Rank(1),
+                })
+                .collect(),
+            groups,
+            free_slots: FreeSlots::Forbidden,
+            ..AllocationProblem::default()
+        }
+    }
+
+    #[test]
+    fn seats_hard_together_group_in_one_room() {
+        let group = ParticipantGroup {
+            participants: vec!["carl".to_owned(), "dora".to_owned()],
+            relation: GroupRelation::Together,
+            penalty: None,
+        };
+        let problem = problem(2, &["anna", "ben", "carl", "dora", "emil"], vec![group]);
+        let allocation = solve(&problem, &SolverOptions::default()).unwrap();
+
+        assert_eq!(allocation.workshops.len(), 2);
+        assert_eq!(allocation.assignments.len(), 5);
+        let room_of = |participant: &str| {
+            allocation
+                .assignments
+                .iter()
+                .find(|assignment| assignment.participant == participant)
+                .map(|assignment| assignment.room.clone())
+        };
+        assert_eq!(room_of("carl"), room_of("dora"));
+    }
+
+    #[test]
+    fn diagnoses_hard_apart_group() {
+        let group = ParticipantGroup {
+            participants: vec!["anna".to_owned(), "ben".to_owned()],
+            relation: GroupRelation::Apart,
+            penalty: None,
+        };
+        let Err(OptimizerError::Infeasible(report)) = solve(
+            &problem(1, &["anna", "ben"], vec![group.clone()]),
+            &SolverOptions::default(),
+        ) else {
+            panic!("problem should be infeasible");
+        };
+        assert!(report.conflicting.contains(&HardConstraint::Group {
+            participants: group.participants,
+            relation: group.relation,
+        }));
+    }
+
+    fn pairs(pairs: &[[&str; 2]]) -> Vec<ParticipantGroup> {
+        pairs
+            .iter()
+            .map(|pair| ParticipantGroup {
+                participants: pair
+                    .iter()
+                    .map(|participant| (*participant).to_owned())
+                    .collect(),
+                relation: GroupRelation::Together,
+                penalty: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn leaves_hard_together_group_out_if_it_does_not_fit_into_a_room() {
+        let groups = pairs(&[["a1", "a2"], ["b1", "b2"], ["c1", "c2"]]);
+        let mut problem = problem(2, &["a1", "a2", "b1", "b2", "c1", "c2"], groups);
+        problem.free_slots = FreeSlots::Allowed;
+        let allocation = solve(&problem, &SolverOptions::default()).unwrap();
+
+        assert_eq!(allocation.assignments.len(), 4);
+        for pair in &problem.groups {
+            let rooms: BTreeSet<&str> = allocation
+                .assignments
+                .iter()
+                .filter(|assignment| pair.participants.contains(&assignment.participant))
+                .map(|assignment| assignment.room.as_str())
+                .collect();
+            assert!(rooms.len() <= 1);
+        }
+
+        problem.free_slots = FreeSlots::Forbidden;
+        let Err(OptimizerError::Infeasible(report)) = solve(&problem, &SolverOptions::default())
+        else {
+            panic!("problem should be infeasible");
+        };
+        assert!(report.conflicting.contains(&HardConstraint::Seating {
+            timeslot: "morning".to_owned(),
+            topic: "topic".to_owned(),
+        }));
+    }
+}
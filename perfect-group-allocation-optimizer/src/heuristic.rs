@@ -0,0 +1,509 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::cmp::Reverse;
+
+use itertools::Itertools;
+
+use crate::groups::seat_participants;
+use crate::model::{
+    by_identifier, fulfills_requirements, rooms_in_timeslots, rooms_spanning, spanned_timeslots,
+    Enforcement, RoomInTimeSlot, WorkshopModel,
+};
+use crate::quotas::{quota_terms, QuotaBucket};
+use crate::{
+    Allocation, AllocationProblem, Assignment, FreeSlots, GroupRelation, OptimizerError, Rank,
+    Room, ScheduledWorkshop, Timeslot, WorkshopTopic,
+};
+
+/// A workshop placed in a room by the heuristic.
+struct PlacedWorkshop<'a> {
+    topic: &'a WorkshopTopic,
+    /// the timeslots the workshop spans, starting with the one it starts in
+    timeslots: &'a [Timeslot],
+    room: RoomInTimeSlot<'a>,
+}
+
+impl<'a> PlacedWorkshop<'a> {
+    fn capacity(&self) -> usize {
+        self.room.capacity(self.topic).into()
+    }
+
+    fn timeslot(&self) -> &'a str {
+        &self.timeslots[0].identifier
+    }
+}
+
+/// The rooms that are still free and the workshops placed so far.
+struct Schedule<'a> {
+    /// all timeslots in chronological order
+    timeslots: &'a [Timeslot],
+    free_rooms: BTreeMap<&'a str, Vec<RoomInTimeSlot<'a>>>,
+    workshops: Vec<PlacedWorkshop<'a>>,
+}
+
+impl<'a> Schedule<'a> {
+    /// Places a workshop of `topic` starting in `timeslot` in the largest suitable room
+    /// that is free in all timeslots it spans.
+    fn place(&mut self, topic: &'a WorkshopTopic, timeslot: &str) -> bool {
+        let Some(spanned) = spanned_timeslots(self.timeslots, topic, timeslot) else {
+            return false;
+        };
+        let Some(room) = rooms_spanning(&self.free_rooms, spanned)
+            .into_iter()
+            .filter(|room_in_timeslot| fulfills_requirements(room_in_timeslot.room, topic))
+            .max_by_key(|room_in_timeslot| room_in_timeslot.capacity(topic))
+        else {
+            return false;
+        };
+        for spanned_timeslot in spanned {
+            if let Some(rooms) = self
+                .free_rooms
+                .get_mut(spanned_timeslot.identifier.as_str())
+            {
+                rooms.retain(|free| free.room.identifier != room.room.identifier);
+            }
+        }
+        self.workshops.push(PlacedWorkshop {
+            topic,
+            timeslots: spanned,
+            room,
+        });
+        true
+    }
+
+    /// Places a repetition of `topic` in the timeslot with the fewest workshops of it.
+    fn place_repetition(&mut self, topic: &'a WorkshopTopic) -> bool {
+        // sorting collects the timeslots, so the rooms can be changed while iterating
+        let mut timeslots = self
+            .free_rooms
+            .keys()
+            .copied()
+            .sorted_by_key(|timeslot| self.workshops_starting(topic, timeslot).count());
+        timeslots.any(|timeslot| self.place(topic, timeslot))
+    }
+
+    fn workshops_of<'s>(
+        &'s self,
+        topic: &'s WorkshopTopic,
+    ) -> impl Iterator<Item = &'s PlacedWorkshop<'a>> + Clone + 's {
+        self.workshops
+            .iter()
+            .filter(|workshop| workshop.topic.identifier == topic.identifier)
+    }
+
+    fn workshops_starting<'s>(
+        &'s self,
+        topic: &'s WorkshopTopic,
+        timeslot: &'s str,
+    ) -> impl Iterator<Item = &'s PlacedWorkshop<'a>> + 's {
+        self.workshops_of(topic)
+            .filter(move |workshop| workshop.timeslot() == timeslot)
+    }
+}
+
+/// Allocates the participants of `problem` greedily without solving an ILP, which takes
+/// milliseconds even for large problems but usually results in a worse allocation than [`crate::solve`].
+///
+/// The fixed workshops and the minimal repetitions of the other topics are placed in the largest
+/// suitable rooms first, further repetitions are added for the topics with the most unmet demand.
+/// Then the best ranked preferences are fulfilled first, groups that have to be allocated together
+/// are only assigned together and nobody attends a topic twice. Soft group constraints and quotas
+/// are ignored but their penalties are subtracted from the objective. The allocation is always
+/// feasible, see [`crate::SolverOptions::warm_start`].
+///
+/// Fails with [`OptimizerError::HeuristicFailed`] if the workshops that need to be held don't fit
+/// into the rooms this way, if [`FreeSlots::Forbidden`] and a participant is left without
+/// a workshop or if a hard [`crate::Quota`] is violated, the problem may still be feasible.
+pub fn heuristic(problem: &AllocationProblem) -> Result<Allocation, OptimizerError> {
+    // reports the same errors as the model
+    WorkshopModel::new(problem, &Enforcement::All)?;
+    let timeslots = by_identifier(&problem.timeslots, |timeslot| &timeslot.identifier)?;
+    let topics = by_identifier(&problem.topics, |topic| &topic.identifier)?;
+    let participants = by_identifier(&problem.participants, |participant| &participant.identifier)?;
+
+    let mut schedule = Schedule {
+        timeslots: &problem.timeslots,
+        free_rooms: rooms_in_timeslots(problem)?,
+        workshops: Vec::new(),
+    };
+
+    // like the model the heuristic doesn't depend on the order of the input
+    for workshop in problem
+        .workshops
+        .iter()
+        .sorted_by_key(|workshop| (&workshop.timeslot, &workshop.topic))
+    {
+        if !schedule.place(topics[workshop.topic.as_str()], &workshop.timeslot) {
+            return Err(OptimizerError::HeuristicFailed);
+        }
+    }
+
+    // the best rank of every participant for every topic
+    let mut preferences: BTreeMap<(&str, &str), Rank> = BTreeMap::new();
+    for preference in &problem.preferences {
+        let rank = preferences
+            .entry((&preference.participant, &preference.topic))
+            .or_insert(preference.rank);
+        *rank = (*rank).max(preference.rank);
+    }
+    // how many participants would like to attend the topic
+    let demand = |topic: &WorkshopTopic| {
+        preferences
+            .keys()
+            .filter(|(_, preferred)| *preferred == topic.identifier)
+            .count()
+    };
+
+    let scheduled_topics: Vec<&WorkshopTopic> = topics
+        .values()
+        .filter(|topic| topic.repetitions.is_some())
+        .copied()
+        .sorted_by_key(|topic| Reverse(demand(topic)))
+        .collect();
+    for topic in &scheduled_topics {
+        for _ in 0..topic.repetitions.map_or(0, |repetitions| repetitions.min) {
+            if !schedule.place_repetition(topic) {
+                return Err(OptimizerError::HeuristicFailed);
+            }
+        }
+    }
+    // topics for which no further repetition fits
+    let mut saturated = BTreeSet::new();
+    loop {
+        let most_unmet = scheduled_topics
+            .iter()
+            .filter(|topic| !saturated.contains(topic.identifier.as_str()))
+            .filter(|topic| {
+                let max = topic.repetitions.map_or(0, |repetitions| repetitions.max);
+                schedule.workshops_of(topic).count() < max.into()
+            })
+            .map(|topic| {
+                let capacity: usize = schedule
+                    .workshops_of(topic)
+                    .map(PlacedWorkshop::capacity)
+                    .sum();
+                (demand(topic).saturating_sub(capacity), *topic)
+            })
+            .filter(|(unmet, _)| *unmet > 0)
+            .max_by_key(|(unmet, topic)| (*unmet, Reverse(&topic.identifier)));
+        let Some((_, topic)) = most_unmet else {
+            break;
+        };
+        if !schedule.place_repetition(topic) {
+            saturated.insert(topic.identifier.as_str());
+        }
+    }
+    schedule.workshops.sort_by(|a, b| {
+        (a.timeslot(), &a.topic.identifier, &a.room.room.identifier).cmp(&(
+            b.timeslot(),
+            &b.topic.identifier,
+            &b.room.room.identifier,
+        ))
+    });
+
+    // participants that have to be assigned together, in the order of their first member
+    let mut units: Vec<BTreeSet<&str>> = participants
+        .keys()
+        .map(|participant| BTreeSet::from([*participant]))
+        .collect();
+    for group in problem
+        .groups
+        .iter()
+        .filter(|group| group.relation == GroupRelation::Together && group.penalty.is_none())
+    {
+        let (mut merged, others): (Vec<BTreeSet<&str>>, _) = units.into_iter().partition(|unit| {
+            group
+                .participants
+                .iter()
+                .any(|participant| unit.contains(participant.as_str()))
+        });
+        units = others;
+        if let Some(first) = merged.pop() {
+            units.push(merged.into_iter().fold(first, |mut first, unit| {
+                first.extend(unit);
+                first
+            }));
+        }
+    }
+    units.sort();
+    let hard_apart: Vec<BTreeSet<&str>> = problem
+        .groups
+        .iter()
+        .filter(|group| group.relation == GroupRelation::Apart && group.penalty.is_none())
+        .map(|group| group.participants.iter().map(String::as_str).collect())
+        .collect();
+
+    // the workshops of a topic starting in a timeslot are filled together like in the model
+    let mut buckets: Vec<((&[Timeslot], &WorkshopTopic), usize)> = Vec::new();
+    for workshop in &schedule.workshops {
+        match buckets.last_mut() {
+            Some(((timeslots, topic), capacity))
+                if timeslots[0].identifier == workshop.timeslot()
+                    && topic.identifier == workshop.topic.identifier =>
+            {
+                *capacity += workshop.capacity();
+            }
+            _ => buckets.push(((workshop.timeslots, workshop.topic), workshop.capacity())),
+        }
+    }
+    let mut attendees: Vec<Vec<&str>> = vec![Vec::new(); buckets.len()];
+
+    // the worst rank of the members of a unit for every bucket they all prefer
+    let mut candidates: Vec<(Rank, usize, usize)> = Vec::new();
+    for (unit_index, unit) in units.iter().enumerate() {
+        for (bucket_index, ((_, topic), _)) in buckets.iter().enumerate() {
+            let ranks: Option<Vec<Rank>> = unit
+                .iter()
+                .map(|participant| {
+                    preferences
+                        .get(&(*participant, topic.identifier.as_str()))
+                        .copied()
+                })
+                .collect();
+            if let Some(rank) = ranks.and_then(|ranks| ranks.into_iter().min()) {
+                candidates.push((rank, unit_index, bucket_index));
+            }
+        }
+    }
+    candidates.sort_by_key(|(rank, unit_index, bucket_index)| {
+        (Reverse(*rank), *unit_index, *bucket_index)
+    });
+
+    // participants are busy in the timeslots they aren't present in
+    let mut busy: BTreeSet<(&str, &str)> = participants
+        .values()
+        .cartesian_product(timeslots.keys())
+        .filter(|(participant, timeslot)| !participant.is_present(timeslot))
+        .map(|(participant, timeslot)| (participant.identifier.as_str(), *timeslot))
+        .collect();
+    let mut attended_topics: BTreeSet<(&str, &str)> = BTreeSet::new();
+    for (_, unit_index, bucket_index) in candidates {
+        let unit = &units[unit_index];
+        let ((spanned, topic), capacity) = buckets[bucket_index];
+        let bucket = &attendees[bucket_index];
+        let fits = bucket.len() + unit.len() <= capacity;
+        let is_free = unit.iter().all(|participant| {
+            spanned
+                .iter()
+                .all(|timeslot| !busy.contains(&(*participant, timeslot.identifier.as_str())))
+                && !attended_topics.contains(&(*participant, topic.identifier.as_str()))
+        });
+        let is_apart = hard_apart.iter().all(|group| {
+            bucket
+                .iter()
+                .chain(unit)
+                .filter(|participant| group.contains(*participant))
+                .count()
+                <= 1
+        });
+        if fits && is_free && is_apart {
+            busy.extend(
+                unit.iter()
+                    .cartesian_product(spanned)
+                    .map(|(participant, timeslot)| (*participant, timeslot.identifier.as_str())),
+            );
+            attended_topics.extend(
+                unit.iter()
+                    .map(|participant| (*participant, topic.identifier.as_str())),
+            );
+            attendees[bucket_index].extend(unit);
+        }
+    }
+
+    if problem.free_slots == FreeSlots::Forbidden
+        && busy.len() < participants.len() * timeslots.len()
+    {
+        return Err(OptimizerError::HeuristicFailed);
+    }
+
+    let attributes = participants
+        .iter()
+        .map(|(identifier, participant)| (*identifier, &participant.attributes))
+        .collect();
+    let quota_buckets: Vec<QuotaBucket<f64>> = buckets
+        .iter()
+        .zip(&attendees)
+        .map(|(((spanned, topic), _), attendees)| QuotaBucket {
+            target: topic.identifier.as_str(),
+            timeslot: Some(spanned[0].identifier.as_str()),
+            held: f64::from(
+                u32::try_from(
+                    schedule
+                        .workshops_starting(topic, &spanned[0].identifier)
+                        .count(),
+                )
+                .unwrap_or(u32::MAX),
+            ),
+            participants: attendees
+                .iter()
+                .map(|participant| (*participant, 1.0))
+                .collect(),
+        })
+        .collect();
+    let mut quota_penalties = 0.0;
+    for term in quota_terms(
+        &problem.quotas,
+        &attributes,
+        &quota_buckets,
+        |topic| topics.contains_key(topic),
+        OptimizerError::UnknownTopic,
+    )? {
+        match term.quota.penalty {
+            None if term.violation() > 0.0 => return Err(OptimizerError::HeuristicFailed),
+            None => {}
+            Some(penalty) => quota_penalties += f64::from(penalty) * term.violation(),
+        }
+    }
+
+    let mut objective = -quota_penalties;
+    let mut assignments = Vec::new();
+    for (((spanned, topic), _), attendees) in buckets.iter().zip(&attendees) {
+        // distribute the participants over the rooms like the model
+        let rooms: Vec<(&Room, usize)> = schedule
+            .workshops_starting(topic, &spanned[0].identifier)
+            .map(|workshop| (workshop.room.room, workshop.capacity()))
+            .collect();
+        for (participant, room) in
+            seat_participants(attendees.iter().copied(), &problem.groups, &rooms)
+        {
+            let rank = preferences[&(participant, topic.identifier.as_str())];
+            objective += f64::from(rank.0) * f64::from(topic.duration.0);
+            assignments.extend(spanned.iter().map(|timeslot| Assignment {
+                participant: participant.to_owned(),
+                topic: topic.identifier.clone(),
+                timeslot: timeslot.identifier.clone(),
+                room: room.identifier.clone(),
+            }));
+        }
+    }
+
+    // the penalties of the violated soft group constraints
+    for group in &problem.groups {
+        let Some(penalty) = group.penalty else {
+            continue;
+        };
+        let violated = attendees.iter().any(|attendees| {
+            let together = group
+                .participants
+                .iter()
+                .filter(|participant| attendees.contains(&participant.as_str()))
+                .count();
+            match group.relation {
+                GroupRelation::Together => together > 0 && together < group.participants.len(),
+                GroupRelation::Apart => together > 1,
+            }
+        });
+        if violated {
+            objective -= f64::from(penalty);
+        }
+    }
+
+    Ok(Allocation {
+        workshops: schedule
+            .workshops
+            .iter()
+            .flat_map(|workshop| {
+                workshop.timeslots.iter().map(|timeslot| ScheduledWorkshop {
+                    topic: workshop.topic.identifier.clone(),
+                    timeslot: timeslot.identifier.clone(),
+                    room: workshop.room.room.identifier.clone(),
+                })
+            })
+            .collect(),
+        assignments,
+        objective,
+        gap: None,
+        lottery: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+    use core::time::Duration;
+    use std::collections::HashMap;
+
+    use good_lp::Variable;
+
+    use super::heuristic;
+    use crate::model::WorkshopModel;
+    use crate::objective::RankedModel;
+    use crate::{
+        solve, AllocationProblem, Backend, Participant, Preference, Rank, Room, RoomSize,
+        SolverOptions, Timeslot, Workshop, WorkshopDuration, WorkshopTopic, WorkshopTopicSize,
+    };
+
+    /// four participants that prefer one of two workshops which both have two seats
+    fn two_workshops() -> AllocationProblem {
+        let topics = ["chess", "choir"];
+        let participants = ["anna", "ben", "carl", "dora"];
+        AllocationProblem {
+            timeslots: vec![Timeslot {
+                identifier: "morning".to_owned(),
+            }],
+            rooms: ["hall", "lab"]
+                .map(|identifier| Room {
+                    identifier: identifier.to_owned(),
+                    requirements: vec![],
+                    max_size: RoomSize(2),
+                })
+                .into(),
+            topics: topics
+                .map(|identifier| WorkshopTopic {
+                    identifier: identifier.to_owned(),
+                    requirements: vec![],
+                    max_size: WorkshopTopicSize(2),
+                    repetitions: None,
+                    duration: WorkshopDuration(1),
+                })
+                .into(),
+            workshops: topics
+                .map(|topic| Workshop {
+                    topic: topic.to_owned(),
+                    timeslot: "morning".to_owned(),
+                })
+                .into(),
+            participants: participants
+                .map(|identifier| Participant {
+                    identifier: identifier.to_owned(),
+                    timeslots: None,
+                    attributes: BTreeMap::new(),
+                })
+                .into(),
+            preferences: participants
+                .iter()
+                .enumerate()
+                .flat_map(|(index, participant)| {
+                    topics.iter().map(move |topic| Preference {
+                        participant: (*participant).to_owned(),
+                        topic: (*topic).to_owned(),
+                        rank: Rank(u8::from((index % 2 == 0) == (*topic == "chess"))),
+                    })
+                })
+                .collect(),
+            ..AllocationProblem::default()
+        }
+    }
+
+    #[test]
+    fn warm_start_is_returned_if_time_limit_is_reached() {
+        let problem = two_workshops();
+        let allocation = heuristic(&problem).unwrap();
+        let options = SolverOptions {
+            backend: Backend::MicroLp,
+            time_limit: Some(Duration::ZERO),
+            warm_start: true,
+            ..SolverOptions::default()
+        };
+        assert_eq!(solve(&problem, &options).unwrap(), allocation);
+    }
+
+    #[test]
+    fn warm_start_is_a_feasible_initial_solution() {
+        let problem = two_workshops();
+        let allocation = heuristic(&problem).unwrap();
+        let mut model = WorkshopModel::build(&problem).unwrap();
+        let values: HashMap<Variable, f64> =
+            model.initial_solution(&allocation).into_iter().collect();
+        assert!(model.program().is_feasible(&values));
+    }
+}
@@ -0,0 +1,137 @@
+extern crate alloc;
+
+mod allocation;
+mod diagnosis;
+mod error;
+pub mod examples;
+#[cfg(feature = "cli")]
+pub mod files;
+mod groups;
+mod heuristic;
+mod model;
+mod network;
+mod objective;
+mod problem;
+mod program;
+pub mod projects;
+mod quotas;
+mod report;
+mod solver;
+
+pub use allocation::{Allocation, Assignment, ScheduledWorkshop};
+pub use diagnosis::{HardConstraint, InfeasibilityReport};
+#[cfg(feature = "cli")]
+pub use error::FileError;
+pub use error::OptimizerError;
+pub use groups::{GroupRelation, ParticipantGroup};
+pub use heuristic::heuristic;
+pub use objective::Objective;
+pub use problem::{
+    AllocationProblem, FreeSlots, Participant, Preference, Rank, Repetitions, Requirement, Room,
+    RoomAvailability, RoomSize, Timeslot, Workshop, WorkshopDuration, WorkshopTopic,
+    WorkshopTopicSize,
+};
+pub use quotas::{Quota, QuotaLimit};
+pub use report::{
+    AllocationReport, TimeslotUtilisation, TopicUtilisation, UnassignedParticipant,
+    WorkshopUtilisation,
+};
+pub use solver::{Backend, SolverOptions};
+
+use std::io::{BufRead, Write};
+
+use good_lp::ResolutionError;
+
+use crate::diagnosis::diagnose;
+use crate::model::{Enforcement, WorkshopModel};
+use crate::objective::optimize;
+use crate::solver::SolverRuns;
+
+/// Allocates the participants of `problem` to workshops according to its [`Objective`]
+/// using the solver configured in `options`.
+///
+/// If the problem is infeasible an [`InfeasibilityReport`] is returned in [`OptimizerError::Infeasible`].
+pub fn solve(
+    problem: &AllocationProblem,
+    options: &SolverOptions,
+) -> Result<Allocation, OptimizerError> {
+    solve_with_previous(problem, None, options)
+}
+
+/// Like [`solve`] but moves as few participants of the `previous` allocation as possible,
+/// e.g. to take late changes of the problem into account after it was published.
+///
+/// Participants only count as moved if they attend another topic or timeslot. The rooms are
+/// chosen anew, so a participant may stay in a workshop that is held in another room now.
+pub fn resolve(
+    problem: &AllocationProblem,
+    previous: &Allocation,
+    options: &SolverOptions,
+) -> Result<Allocation, OptimizerError> {
+    solve_with_previous(problem, Some(previous), options)
+}
+
+fn solve_with_previous(
+    problem: &AllocationProblem,
+    previous: Option<&Allocation>,
+    options: &SolverOptions,
+) -> Result<Allocation, OptimizerError> {
+    let runs = SolverRuns::start(options);
+    // the heuristic doesn't try to keep the previous assignments
+    let warm_start = if options.warm_start && previous.is_none() {
+        match heuristic(problem) {
+            Ok(allocation) => Some(allocation),
+            Err(OptimizerError::HeuristicFailed) => None,
+            Err(error) => return Err(error),
+        }
+    } else {
+        None
+    };
+    match optimize::<WorkshopModel>(
+        problem,
+        problem.objective,
+        previous,
+        warm_start
+            .as_ref()
+            .map(|allocation| (allocation, allocation.objective)),
+        &runs,
+    ) {
+        Ok(solved) => Ok(Allocation {
+            lottery: options.lottery,
+            ..solved.allocation()
+        }),
+        Err(OptimizerError::TimeLimitReached) => warm_start.ok_or(OptimizerError::TimeLimitReached),
+        Err(OptimizerError::Solver(ResolutionError::Infeasible)) => {
+            Err(OptimizerError::Infeasible(diagnose(problem, &runs)?))
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Checks that `problem` is well-formed without solving it.
+///
+/// A valid problem may still be infeasible, see [`solve`].
+pub fn validate(problem: &AllocationProblem) -> Result<(), OptimizerError> {
+    WorkshopModel::new(problem, &Enforcement::All).map(drop)
+}
+
+/// Writes the model of `problem` maximizing the summed rank in the CPLEX LP format,
+/// e.g. to solve it with another solver.
+pub fn export_lp(problem: &AllocationProblem, writer: impl Write) -> Result<(), OptimizerError> {
+    WorkshopModel::new(problem, &Enforcement::All)?.write_lp(writer)
+}
+
+/// Like [`export_lp`] but in the free MPS format.
+pub fn export_mps(problem: &AllocationProblem, writer: impl Write) -> Result<(), OptimizerError> {
+    WorkshopModel::new(problem, &Enforcement::All)?.write_mps(writer)
+}
+
+/// Reads the solution of an external solver for the model written by [`export_lp`] or [`export_mps`].
+pub fn import_solution(
+    problem: &AllocationProblem,
+    reader: impl BufRead,
+) -> Result<Allocation, OptimizerError> {
+    Ok(WorkshopModel::new(problem, &Enforcement::All)?
+        .read_solution(reader)?
+        .allocation())
+}
@@ -1,166 +1,245 @@
-extern crate alloc;
-
-mod examples;
-
-use alloc::collections::BTreeMap;
-
-use good_lp::solvers::ObjectiveDirection::Maximisation;
-use good_lp::{default_solver, variable, Expression, ProblemVariables, Solution, SolverModel};
-use itertools::Itertools;
-use tracing::info;
-
-use crate::examples::workshops::{
-    Participant, Preference, Rank, Requirement, Room, RoomInTimeSlot, RoomSize, Timeslot, Workshop,
-    WorkshopTopic, WorkshopTopicSize,
+use std::fs::File;
+use std::io::{stdout, BufReader, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use perfect_group_allocation_optimizer::examples::workshops;
+use perfect_group_allocation_optimizer::files::{
+    read_allocation, read_problem, write_allocation_json, write_assignments_csv,
+    write_problem_json, write_report_json,
+};
+use perfect_group_allocation_optimizer::{
+    export_lp, export_mps, heuristic, import_solution, resolve, solve, validate, Allocation,
+    AllocationProblem, AllocationReport, Backend, SolverOptions,
 };
 
-#[allow(unused)]
-fn group_pairs<A, B, I>(items: I) -> BTreeMap<A, Vec<B>>
-where
-    A: Ord,
-    I: IntoIterator<Item = (A, B)>,
-{
-    items
-        .into_iter()
-        .fold(BTreeMap::new(), |mut acc, (key, value)| {
-            acc.entry(key).or_default().push(value);
-            acc
-        })
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Csv,
 }
 
-pub fn main() {
-    let mut variables = ProblemVariables::new();
+#[derive(Clone, Copy, ValueEnum)]
+enum ModelFormat {
+    Lp,
+    Mps,
+}
 
-    let requirement_outside = Requirement {
-        identifier: "Outside".to_owned(),
-    };
-    let requirement_computer_pool = Requirement {
-        identifier: "Computer-Pool".to_owned(),
-    };
-    let _requirements: Vec<&Requirement> = vec![&requirement_outside, &requirement_computer_pool];
+#[derive(Subcommand)]
+enum Command {
+    /// Allocates the participants of a problem
+    Solve {
+        /// a JSON file or a directory of CSV files
+        problem: PathBuf,
+        /// `json` writes the whole allocation, `csv` only the assignments
+        #[arg(long, value_enum, default_value = "json")]
+        format: Format,
+        /// defaults to stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// e.g. `cbc`, `highs` or `microlp`, depending on the enabled features
+        #[arg(long)]
+        solver: Option<Backend>,
+        /// the same problem, solver and seed always result in the same allocation
+        #[arg(long, default_value_t = SolverOptions::DEFAULT_SEED)]
+        seed: u32,
+        /// randomly chooses between equally good allocations
+        #[arg(long)]
+        lottery: bool,
+        /// the seed of the lottery, generated if not given
+        #[arg(long, requires = "lottery")]
+        lottery_seed: Option<u64>,
+        /// in seconds for all solver runs together, afterwards the best allocation found so far is used
+        #[arg(long)]
+        time_limit: Option<f64>,
+        /// stops once the allocation is proven to be within this relative gap of the optimum
+        #[arg(long)]
+        mip_gap: Option<f64>,
+        #[arg(long)]
+        threads: Option<u32>,
+        /// starts from a greedy allocation, which is also used if the time limit is reached
+        #[arg(long)]
+        warm_start: bool,
+        /// writes statistics about the allocation as JSON to this file
+        #[arg(long)]
+        report: Option<PathBuf>,
+        /// a published allocation as JSON, as few of its participants as possible are moved
+        #[arg(long)]
+        previous: Option<PathBuf>,
+    },
+    /// Allocates the participants of a problem greedily without a solver
+    Heuristic {
+        /// a JSON file or a directory of CSV files
+        problem: PathBuf,
+        /// `json` writes the whole allocation, `csv` only the assignments
+        #[arg(long, value_enum, default_value = "json")]
+        format: Format,
+        /// defaults to stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// writes statistics about the allocation as JSON to this file
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
+    /// Writes the model of a problem to solve it with another solver
+    Export {
+        /// a JSON file or a directory of CSV files
+        problem: PathBuf,
+        #[arg(long, value_enum, default_value = "lp")]
+        format: ModelFormat,
+        /// defaults to stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Reads the solution of another solver for an exported model
+    Import {
+        /// the problem the model was exported from
+        problem: PathBuf,
+        /// lines with variable names and their values
+        solution: PathBuf,
+        /// `json` writes the whole allocation, `csv` only the assignments
+        #[arg(long, value_enum, default_value = "json")]
+        format: Format,
+        /// defaults to stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// writes statistics about the allocation as JSON to this file
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
+    /// Checks that a problem is well-formed without solving it
+    Validate {
+        /// a JSON file or a directory of CSV files
+        problem: PathBuf,
+    },
+    /// Prints an example problem as JSON
+    Example,
+}
 
-    let timeslot_morgens = Timeslot {
-        identifier: "morgens".to_owned(),
-    };
-    let timeslot_mittags = Timeslot {
-        identifier: "mittags".to_owned(),
-    };
-    let timeslot_abends = Timeslot {
-        identifier: "abends".to_owned(),
-    };
-    let timeslots: Vec<&Timeslot> = vec![&timeslot_morgens, &timeslot_mittags, &timeslot_abends];
+/// Allocates participants to workshops according to their preferences.
+#[derive(Parser)]
+#[command(name = "pga-optimize")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
 
-    let room_cpool = Room {
-        identifier: "C-Pool".to_owned(),
-        requirements: vec![&requirement_computer_pool],
-        max_size: RoomSize(128),
-    };
-    let room_bosch = Room {
-        identifier: "Bosch".to_owned(),
-        requirements: vec![],
-        max_size: RoomSize(75),
-    };
-    let room_draussen = Room {
-        identifier: "draussen".to_owned(),
-        requirements: vec![&requirement_outside],
-        max_size: RoomSize(200),
-    };
-    let rooms: Vec<&Room> = vec![&room_cpool, &room_bosch, &room_draussen];
-    let rooms_in_timeslot: Vec<RoomInTimeSlot> = rooms
-        .iter()
-        .flat_map(|room| {
-            timeslots
-                .iter()
-                .map(|timeslot| RoomInTimeSlot { room, timeslot })
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| {
+            duration.as_secs() ^ u64::from(duration.subsec_nanos())
         })
-        .collect();
-
-    let workshop_topic_linux = WorkshopTopic {
-        identifier: "linux-lernen".to_owned(),
-        requirements: vec![&requirement_computer_pool],
-        max_size: WorkshopTopicSize(50),
-    };
-    let workshop_topics: Vec<&WorkshopTopic> = vec![&workshop_topic_linux];
-
-    let workshops: Vec<Workshop> = vec![Workshop {
-        topic: workshop_topics[0],
-        timeslot: timeslots[0],
-    }];
-
-    let participant_moritz = Participant {
-        identifier: "moritz".to_owned(),
-    };
-    let _participants: Vec<&Participant> = vec![&participant_moritz];
-
-    let _preferences: Vec<&Preference> = vec![&Preference {
-        participant: &participant_moritz,
-        topic: &workshop_topic_linux,
-        rank: Rank(0),
-    }];
-
-    // this could be done in the database later
-    let rooms_in_timeslot: BTreeMap<&Timeslot, Vec<&RoomInTimeSlot>> = rooms_in_timeslot
-        .iter()
-        .into_group_map_by(|room_in_timeslot| room_in_timeslot.timeslot)
-        .into_iter()
-        .collect();
-
-    let workshops_in_timeslot: BTreeMap<&Timeslot, Vec<&Workshop>> = workshops
-        .iter()
-        .into_group_map_by(|workshop| workshop.timeslot)
-        .into_iter()
-        .collect();
-
-    let grouped_by_timeslot = rooms_in_timeslot
-        .into_iter()
-        .merge_join_by(workshops_in_timeslot, |left, right| left.0.cmp(right.0))
-        .map(|value| {
-            (
-                value
-                    .clone()
-                    .map_any(|val| val.0, |val| val.0)
-                    .reduce(|left, _right| left),
-                value.map_any(|val| val.1, |val| val.1).or_default(),
-            )
-        });
-
-    info!("{grouped_by_timeslot:#?}");
-
-    grouped_by_timeslot.for_each(|(timeslot, (rooms_in_timeslot, workshops_in_timeslot))| {
-        let combinations = rooms_in_timeslot
-            .into_iter()
-            .cartesian_product(workshops_in_timeslot);
-        combinations.for_each(|(room_in_timeslot, workshop_in_timeslot)| {
-            variables.add(
-                variable()
-                    .name(
-                        timeslot.identifier.clone()
-                            + "_"
-                            + &room_in_timeslot.room.identifier
-                            + &workshop_in_timeslot.topic.identifier,
-                    )
-                    .binary(),
-            );
-        });
-    });
-
-    // RoomInTimeSlot <-> Workshop (grouping by timeslot)
-    // Participant <-> Workshop (per timeslot)
-    // restricting WorkshopTopic only once
-    // maximizing WorkshopTopic fullfilled times rank
-
-    let test = variables.add(variable().name("test").binary());
-    let objective: Expression = test + 1;
-
-    info!("{}", variables.display(&objective));
+}
 
-    let problem = variables.optimise(Maximisation, objective);
+fn output_writer(output: Option<PathBuf>) -> std::io::Result<Box<dyn Write>> {
+    Ok(match output {
+        Some(output) => Box::new(File::create(output)?),
+        None => Box::new(stdout()),
+    })
+}
 
-    let solution = problem
-        .using(default_solver)
-        .with(test << 3_i32)
-        .solve()
-        .unwrap();
+fn write_allocation(
+    problem: &AllocationProblem,
+    allocation: &Allocation,
+    format: Format,
+    output: Option<PathBuf>,
+    report: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let writer = output_writer(output)?;
+    match format {
+        Format::Json => write_allocation_json(allocation, writer)?,
+        Format::Csv => write_assignments_csv(allocation, writer)?,
+    }
+    if let Some(report) = report {
+        write_report_json(
+            &AllocationReport::new(problem, allocation),
+            File::create(report)?,
+        )?;
+    }
+    Ok(())
+}
 
-    info!("{}", solution.value(test));
+pub fn main() -> Result<(), Box<dyn std::error::Error>> {
+    match Cli::parse().command {
+        Command::Solve {
+            problem,
+            format,
+            output,
+            solver,
+            seed,
+            lottery,
+            lottery_seed,
+            time_limit,
+            mip_gap,
+            threads,
+            warm_start,
+            report,
+            previous,
+        } => {
+            let options = SolverOptions {
+                backend: solver.unwrap_or_default(),
+                seed,
+                lottery: lottery.then(|| lottery_seed.unwrap_or_else(random_seed)),
+                time_limit: time_limit.map(Duration::try_from_secs_f64).transpose()?,
+                mip_gap,
+                threads,
+                warm_start,
+            };
+            if let Some(lottery) = options.lottery {
+                eprintln!("lottery seed {lottery}");
+            }
+            let problem = read_problem(&problem)?;
+            let allocation = match previous {
+                Some(previous) => resolve(&problem, &read_allocation(&previous)?, &options)?,
+                None => solve(&problem, &options)?,
+            };
+            eprintln!("fingerprint {}", allocation.fingerprint());
+            if let Some(gap) = allocation.gap {
+                eprintln!("gap {gap}");
+            }
+            write_allocation(&problem, &allocation, format, output, report)?;
+        }
+        Command::Heuristic {
+            problem,
+            format,
+            output,
+            report,
+        } => {
+            let problem = read_problem(&problem)?;
+            let allocation = heuristic(&problem)?;
+            eprintln!("fingerprint {}", allocation.fingerprint());
+            write_allocation(&problem, &allocation, format, output, report)?;
+        }
+        Command::Export {
+            problem,
+            format,
+            output,
+        } => {
+            let problem = read_problem(&problem)?;
+            let writer = output_writer(output)?;
+            match format {
+                ModelFormat::Lp => export_lp(&problem, writer)?,
+                ModelFormat::Mps => export_mps(&problem, writer)?,
+            }
+        }
+        Command::Import {
+            problem,
+            solution,
+            format,
+            output,
+            report,
+        } => {
+            let problem = read_problem(&problem)?;
+            let allocation = import_solution(&problem, BufReader::new(File::open(solution)?))?;
+            write_allocation(&problem, &allocation, format, output, report)?;
+        }
+        Command::Validate { problem } => {
+            validate(&read_problem(&problem)?)?;
+            println!("{} is valid", problem.display());
+        }
+        Command::Example => write_problem_json(&workshops::example(), stdout())?,
+    }
+    Ok(())
 }
@@ -0,0 +1,1117 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use std::io::{BufRead, Write};
+
+use good_lp::{Expression, Solution, Variable};
+use itertools::Itertools;
+
+use crate::diagnosis::HardConstraint;
+use crate::groups::{constrain_groups, seat_participants, together_units};
+use crate::objective::{ParticipantSlots, RankedModel, SolvedModel};
+use crate::program::{eq, leq, Domain, LinearConstraint, LinearProgram, Sense};
+use crate::quotas::{constrain_quotas, quota_terms, QuotaBucket};
+use crate::solver::{maximise, Solved, SolverRuns};
+use crate::{
+    Allocation, AllocationProblem, Assignment, FreeSlots, GroupRelation, OptimizerError,
+    Participant, ParticipantGroup, Rank, Room, RoomSize, ScheduledWorkshop, Timeslot,
+    WorkshopTopic,
+};
+
+pub fn group_pairs<A, B, I>(items: I) -> BTreeMap<A, Vec<B>>
+where
+    A: Ord,
+    I: IntoIterator<Item = (A, B)>,
+{
+    items
+        .into_iter()
+        .fold(BTreeMap::new(), |mut acc, (key, value)| {
+            acc.entry(key).or_default().push(value);
+            acc
+        })
+}
+
+pub fn by_identifier<T>(
+    items: &[T],
+    identifier: fn(&T) -> &str,
+) -> Result<BTreeMap<&str, &T>, OptimizerError> {
+    let mut result = BTreeMap::new();
+    for item in items {
+        if result.insert(identifier(item), item).is_some() {
+            return Err(OptimizerError::DuplicateIdentifier(
+                identifier(item).to_owned(),
+            ));
+        }
+    }
+    Ok(result)
+}
+
+pub fn fulfills_requirements(room: &Room, topic: &WorkshopTopic) -> bool {
+    topic
+        .requirements
+        .iter()
+        .all(|requirement| room.requirements.contains(requirement))
+}
+
+/// Not every room may be available in every timeslot, see [`crate::RoomAvailability`]
+#[derive(Debug, Clone, Copy)]
+pub struct RoomInTimeSlot<'a> {
+    pub room: &'a Room,
+    pub timeslot: &'a Timeslot,
+    /// the size of the room in this timeslot
+    pub max_size: RoomSize,
+}
+
+impl RoomInTimeSlot<'_> {
+    /// how many participants of `topic` fit into the room
+    pub fn capacity(&self, topic: &WorkshopTopic) -> u8 {
+        self.max_size.0.min(topic.max_size.0)
+    }
+}
+
+/// The rooms available in every timeslot, ordered by their identifiers.
+pub fn rooms_in_timeslots(
+    problem: &AllocationProblem,
+) -> Result<BTreeMap<&str, Vec<RoomInTimeSlot<'_>>>, OptimizerError> {
+    let timeslots = by_identifier(&problem.timeslots, |timeslot| &timeslot.identifier)?;
+    let rooms = by_identifier(&problem.rooms, |room| &room.identifier)?;
+
+    let mut availability: BTreeMap<&str, BTreeMap<&str, Option<RoomSize>>> = BTreeMap::new();
+    for available in &problem.room_availability {
+        if !rooms.contains_key(available.room.as_str()) {
+            return Err(OptimizerError::UnknownRoom(available.room.clone()));
+        }
+        if !timeslots.contains_key(available.timeslot.as_str()) {
+            return Err(OptimizerError::UnknownTimeslot(available.timeslot.clone()));
+        }
+        if availability
+            .entry(available.room.as_str())
+            .or_default()
+            .insert(available.timeslot.as_str(), available.max_size)
+            .is_some()
+        {
+            return Err(OptimizerError::DuplicateIdentifier(format!(
+                "{} in {}",
+                available.room, available.timeslot
+            )));
+        }
+    }
+
+    Ok(timeslots
+        .iter()
+        .map(|(identifier, timeslot)| {
+            let available_rooms = rooms
+                .values()
+                .filter_map(|room| {
+                    let max_size = match availability.get(room.identifier.as_str()) {
+                        None => room.max_size,
+                        Some(timeslots) => timeslots.get(identifier)?.unwrap_or(room.max_size),
+                    };
+                    Some(RoomInTimeSlot {
+                        room,
+                        timeslot,
+                        max_size,
+                    })
+                })
+                .collect();
+            (*identifier, available_rooms)
+        })
+        .collect())
+}
+
+/// The topic, timeslot and room of every workshop of `allocation` in the timeslot it starts in.
+pub fn workshop_starts<'a>(
+    problem: &AllocationProblem,
+    allocation: &'a Allocation,
+) -> BTreeSet<(&'a str, &'a str, &'a str)> {
+    // the timeslots of the workshops of every topic in every room in chronological order,
+    // a workshop is listed in every timeslot it spans
+    let mut held: BTreeMap<(&str, &str), Vec<&str>> = BTreeMap::new();
+    for timeslot in &problem.timeslots {
+        for workshop in allocation
+            .workshops
+            .iter()
+            .filter(|workshop| workshop.timeslot == timeslot.identifier)
+        {
+            held.entry((&workshop.topic, &workshop.room))
+                .or_default()
+                .push(&workshop.timeslot);
+        }
+    }
+    held.into_iter()
+        .flat_map(|((topic, room), timeslots)| {
+            let duration = problem
+                .topics
+                .iter()
+                .find(|other| other.identifier == topic)
+                .map_or(1, |topic| usize::from(topic.duration.0).max(1));
+            timeslots
+                .chunks(duration)
+                .map(|timeslots| (topic, timeslots[0], room))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// The timeslots a workshop of `topic` starting in `timeslot` is held in, `None` if there aren't
+/// enough `timeslots` after it.
+pub fn spanned_timeslots<'a>(
+    timeslots: &'a [Timeslot],
+    topic: &WorkshopTopic,
+    timeslot: &str,
+) -> Option<&'a [Timeslot]> {
+    let start = timeslots
+        .iter()
+        .position(|other| other.identifier == timeslot)?;
+    timeslots.get(start..start + usize::from(topic.duration.0))
+}
+
+/// The rooms available in all `timeslots` with the smallest size they have in any of them.
+pub fn rooms_spanning<'a>(
+    rooms_in_timeslots: &BTreeMap<&str, Vec<RoomInTimeSlot<'a>>>,
+    timeslots: &[Timeslot],
+) -> Vec<RoomInTimeSlot<'a>> {
+    let Some((first, others)) = timeslots.split_first() else {
+        return Vec::new();
+    };
+    rooms_in_timeslots
+        .get(first.identifier.as_str())
+        .into_iter()
+        .flatten()
+        .filter_map(|room_in_timeslot| {
+            others
+                .iter()
+                .try_fold(*room_in_timeslot, |spanning, timeslot| {
+                    let other = rooms_in_timeslots
+                        .get(timeslot.identifier.as_str())?
+                        .iter()
+                        .find(|other| other.room.identifier == spanning.room.identifier)?;
+                    Some(RoomInTimeSlot {
+                        max_size: RoomSize(spanning.max_size.0.min(other.max_size.0)),
+                        ..spanning
+                    })
+                })
+        })
+        .collect()
+}
+
+/// Which [`HardConstraint`]s are added to the model.
+pub enum Enforcement<'e> {
+    All,
+    /// all of them but with slack variables, the objective minimizes the total slack
+    Elastic,
+    /// only the given ones
+    Only(&'e BTreeSet<HardConstraint>),
+}
+
+/// Adds [`HardConstraint`]s to a model according to the [`Enforcement`].
+pub struct HardConstraints<'e> {
+    enforcement: &'e Enforcement<'e>,
+    added: Vec<HardConstraint>,
+    slacks: Vec<(HardConstraint, Variable)>,
+}
+
+impl<'e> HardConstraints<'e> {
+    pub const fn new(enforcement: &'e Enforcement<'e>) -> Self {
+        Self {
+            enforcement,
+            added: Vec::new(),
+            slacks: Vec::new(),
+        }
+    }
+
+    pub fn add(
+        &mut self,
+        program: &mut LinearProgram,
+        hard_constraint: HardConstraint,
+        mut expression: Expression,
+        sense: Sense,
+        bound: f64,
+    ) {
+        match self.enforcement {
+            Enforcement::All => {}
+            Enforcement::Elastic => {
+                if matches!(sense, Sense::LessOrEqual | Sense::Equal) {
+                    let slack = program
+                        .add_variable(format!("slack_{}", self.slacks.len()), Domain::NonNegative);
+                    expression -= slack;
+                    self.slacks.push((hard_constraint.clone(), slack));
+                }
+                if matches!(sense, Sense::GreaterOrEqual | Sense::Equal) {
+                    let slack = program
+                        .add_variable(format!("slack_{}", self.slacks.len()), Domain::NonNegative);
+                    expression += slack;
+                    self.slacks.push((hard_constraint.clone(), slack));
+                }
+            }
+            Enforcement::Only(enforced) => {
+                if !enforced.contains(&hard_constraint) {
+                    self.added.push(hard_constraint);
+                    return;
+                }
+            }
+        }
+        program.add_constraint(LinearConstraint::new(expression, sense, bound));
+        self.added.push(hard_constraint);
+    }
+}
+
+/// All workshops of a topic starting in a timeslot. Workshops of the same topic in the same timeslot
+/// are interchangeable so they only differ in the room they are held in.
+struct TopicInTimeslot<'a> {
+    topic: &'a WorkshopTopic,
+    /// the timeslots the workshops span, starting with the one they start in
+    timeslots: &'a [Timeslot],
+    /// whether a workshop of this topic is held in the room, which is available in all the timeslots
+    rooms: Vec<(RoomInTimeSlot<'a>, Variable)>,
+    /// whether the participant attends a workshop of this topic
+    participants: Vec<(&'a Participant, Variable)>,
+    /// whether the members of a [`GroupRelation::Together`] unit are seated in the room,
+    /// for every room of `rooms` if there are multiple
+    units: Vec<(BTreeSet<&'a str>, Vec<Variable>)>,
+}
+
+/// Chooses the room of every unit of `groups` attending a topic held in multiple `rooms`
+/// such that the units fit into these rooms as a whole, see [`HardConstraint::Seating`].
+fn seat_units<'a>(
+    program: &mut LinearProgram,
+    hard_constraints: &mut HardConstraints,
+    groups: &[&ParticipantGroup],
+    topic: &WorkshopTopic,
+    rooms: &[(RoomInTimeSlot<'a>, Variable)],
+    participants: &[(&'a Participant, Variable)],
+) -> Vec<(BTreeSet<&'a str>, Vec<Variable>)> {
+    if rooms.len() < 2 {
+        return Vec::new();
+    }
+    let mut seated = vec![Expression::default(); rooms.len()];
+    let units: Vec<(BTreeSet<&str>, Vec<Variable>)> = together_units(
+        participants
+            .iter()
+            .map(|(participant, _)| participant.identifier.as_str()),
+        groups.iter().copied(),
+    )
+    .into_iter()
+    .filter(|unit| unit.len() > 1)
+    .map(|unit| {
+        let first = unit.first().copied().unwrap_or_default();
+        let Some((_, attends)) = participants
+            .iter()
+            .find(|(participant, _)| participant.identifier == first)
+        else {
+            return (unit, Vec::new());
+        };
+        let in_rooms: Vec<Variable> = rooms
+            .iter()
+            .zip(&mut seated)
+            .map(|((room_in_timeslot, workshop_room), seated)| {
+                let in_room = program.add_variable(
+                    "seat_".to_owned()
+                        + &room_in_timeslot.timeslot.identifier
+                        + "_"
+                        + &room_in_timeslot.room.identifier
+                        + "_"
+                        + &topic.identifier
+                        + "_"
+                        + first,
+                    Domain::Binary,
+                );
+                program.add_constraint(leq(in_room, *workshop_room));
+                *seated += f64::from(u32::try_from(unit.len()).unwrap_or(u32::MAX)) * in_room;
+                in_room
+            })
+            .collect();
+        // the unit is seated in one room if its members attend
+        program.add_constraint(eq(in_rooms.iter().sum::<Expression>(), *attends));
+        (unit, in_rooms)
+    })
+    .collect();
+
+    if !units.is_empty() {
+        for ((room_in_timeslot, workshop_room), seated) in rooms.iter().zip(seated) {
+            hard_constraints.add(
+                program,
+                HardConstraint::Seating {
+                    timeslot: room_in_timeslot.timeslot.identifier.clone(),
+                    topic: topic.identifier.clone(),
+                },
+                seated - f64::from(room_in_timeslot.capacity(topic)) * *workshop_room,
+                Sense::LessOrEqual,
+                0.0,
+            );
+        }
+    }
+    units
+}
+
+/// The ILP model of an [`AllocationProblem`].
+pub struct WorkshopModel<'a> {
+    problem: &'a AllocationProblem,
+    program: LinearProgram,
+    topics_in_timeslots: Vec<TopicInTimeslot<'a>>,
+    /// for every participant and timeslot where the participant has a preferred topic
+    participant_slots: ParticipantSlots,
+    top_preferences: Expression,
+    /// to seat the members of a group in the same room
+    groups: &'a [ParticipantGroup],
+    hard_constraints: Vec<HardConstraint>,
+    slacks: Vec<(HardConstraint, Variable)>,
+}
+
+/// A [`WorkshopModel`] together with the solution found by the solver.
+pub struct SolvedWorkshopModel<'a> {
+    solution: Box<dyn Solution>,
+    objective: f64,
+    gap: Option<f64>,
+    topics_in_timeslots: Vec<TopicInTimeslot<'a>>,
+    groups: &'a [ParticipantGroup],
+    hard_constraints: Vec<HardConstraint>,
+    slacks: Vec<(HardConstraint, Variable)>,
+}
+
+impl<'a> WorkshopModel<'a> {
+    pub fn new(
+        problem: &'a AllocationProblem,
+        enforcement: &Enforcement,
+    ) -> Result<Self, OptimizerError> {
+        let requirements =
+            by_identifier(&problem.requirements, |requirement| &requirement.identifier)?;
+        let timeslots = by_identifier(&problem.timeslots, |timeslot| &timeslot.identifier)?;
+        let rooms = by_identifier(&problem.rooms, |room| &room.identifier)?;
+        let topics = by_identifier(&problem.topics, |topic| &topic.identifier)?;
+        let participants =
+            by_identifier(&problem.participants, |participant| &participant.identifier)?;
+
+        if let Some(requirement) = rooms
+            .values()
+            .flat_map(|room| &room.requirements)
+            .chain(topics.values().flat_map(|topic| &topic.requirements))
+            .find(|requirement| !requirements.contains_key(requirement.as_str()))
+        {
+            return Err(OptimizerError::UnknownRequirement(requirement.clone()));
+        }
+        if let Some(timeslot) = participants
+            .values()
+            .flat_map(|participant| participant.timeslots.iter().flatten())
+            .find(|timeslot| !timeslots.contains_key(timeslot.as_str()))
+        {
+            return Err(OptimizerError::UnknownTimeslot(timeslot.clone()));
+        }
+
+        if let Some(topic) = topics.values().find(|topic| topic.duration.0 == 0) {
+            return Err(OptimizerError::InvalidDuration(topic.identifier.clone()));
+        }
+
+        // how many workshops of a topic start in a timeslot, if not decided by the optimizer
+        let mut fixed_workshops: BTreeMap<(&str, &str), u32> = BTreeMap::new();
+        for workshop in &problem.workshops {
+            if !timeslots.contains_key(workshop.timeslot.as_str()) {
+                return Err(OptimizerError::UnknownTimeslot(workshop.timeslot.clone()));
+            }
+            let topic = topics
+                .get(workshop.topic.as_str())
+                .ok_or_else(|| OptimizerError::UnknownTopic(workshop.topic.clone()))?;
+            if topic.repetitions.is_some() {
+                return Err(OptimizerError::ConflictingSchedule(
+                    topic.identifier.clone(),
+                ));
+            }
+            if spanned_timeslots(&problem.timeslots, topic, &workshop.timeslot).is_none() {
+                return Err(OptimizerError::InvalidDuration(topic.identifier.clone()));
+            }
+            *fixed_workshops
+                .entry((workshop.timeslot.as_str(), workshop.topic.as_str()))
+                .or_default() += 1;
+        }
+
+        let unmatched_topics: Vec<String> = topics
+            .values()
+            .filter(|topic| {
+                topic.repetitions.map_or_else(
+                    || {
+                        fixed_workshops
+                            .keys()
+                            .any(|(_, fixed_topic)| *fixed_topic == topic.identifier)
+                    },
+                    |repetitions| repetitions.min > 0,
+                )
+            })
+            .filter(|topic| {
+                !rooms
+                    .values()
+                    .any(|room| fulfills_requirements(room, topic))
+            })
+            .map(|topic| topic.identifier.clone())
+            .collect();
+        if !unmatched_topics.is_empty() {
+            return Err(OptimizerError::UnmatchedRequirements(unmatched_topics));
+        }
+
+        let mut preferences_by_topic: BTreeMap<&str, Vec<(&Participant, Rank)>> = group_pairs(
+            problem
+                .preferences
+                .iter()
+                .map(|preference| {
+                    let participant = participants
+                        .get(preference.participant.as_str())
+                        .ok_or_else(|| {
+                            OptimizerError::UnknownParticipant(preference.participant.clone())
+                        })?;
+                    if !topics.contains_key(preference.topic.as_str()) {
+                        return Err(OptimizerError::UnknownTopic(preference.topic.clone()));
+                    }
+                    Ok((preference.topic.as_str(), (*participant, preference.rank)))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        );
+        // the model must not depend on the order of the input
+        for preferences in preferences_by_topic.values_mut() {
+            preferences.sort_by(|(a, a_rank), (b, b_rank)| {
+                (&a.identifier, a_rank).cmp(&(&b.identifier, b_rank))
+            });
+        }
+
+        let rooms_in_timeslot = rooms_in_timeslots(problem)?;
+
+        let mut program = LinearProgram::default();
+        let mut topics_in_timeslots = Vec::new();
+        let mut participant_slots = ParticipantSlots::default();
+        let mut repetitions_of_topic: BTreeMap<&str, Expression> = BTreeMap::new();
+        // the assignments of every participant to every topic over all timeslots
+        let mut topics_of_participant: BTreeMap<(&str, &str), Vec<(Rank, Variable)>> =
+            BTreeMap::new();
+        let mut hard_constraints = HardConstraints::new(enforcement);
+
+        // the workshops using a room and the workshops a participant attends in every timeslot,
+        // workshops spanning multiple timeslots are listed in all of them
+        let mut topics_of_room: BTreeMap<(&str, &str), Vec<Variable>> = BTreeMap::new();
+        let mut workshops_of_participant: BTreeMap<(&str, &str), Vec<(Rank, Variable)>> =
+            BTreeMap::new();
+        let hard_together_groups: Vec<&ParticipantGroup> = problem
+            .groups
+            .iter()
+            .filter(|group| group.relation == GroupRelation::Together && group.penalty.is_none())
+            .collect();
+
+        for timeslot in timeslots.keys().copied() {
+            // topics with fixed workshops starting in this timeslot and all automatically scheduled topics
+            let topics_in_timeslot = topics.values().filter_map(|topic| {
+                match (
+                    topic.repetitions,
+                    fixed_workshops.get(&(timeslot, topic.identifier.as_str())),
+                ) {
+                    (Some(_), _) => Some((*topic, None)),
+                    (None, Some(count)) => Some((*topic, Some(*count))),
+                    (None, None) => None,
+                }
+            });
+
+            for (topic, fixed_count) in topics_in_timeslot {
+                // automatically scheduled workshops only start where they fit, fixed ones are checked above
+                let Some(spanned) = spanned_timeslots(&problem.timeslots, topic, timeslot) else {
+                    continue;
+                };
+
+                // RoomInTimeSlot <-> Workshop (grouping by the timeslot the workshops start in)
+                let mut rooms_of_topic = Vec::new();
+                let mut held_workshops = Expression::default();
+                let mut capacity = Expression::default();
+                for room_in_timeslot in rooms_spanning(&rooms_in_timeslot, spanned) {
+                    if !fulfills_requirements(room_in_timeslot.room, topic) {
+                        continue;
+                    }
+                    let workshop_room = program.add_variable(
+                        room_in_timeslot.timeslot.identifier.clone()
+                            + "_"
+                            + &room_in_timeslot.room.identifier
+                            + "_"
+                            + &topic.identifier,
+                        Domain::Binary,
+                    );
+                    for spanned_timeslot in spanned {
+                        topics_of_room
+                            .entry((
+                                spanned_timeslot.identifier.as_str(),
+                                room_in_timeslot.room.identifier.as_str(),
+                            ))
+                            .or_default()
+                            .push(workshop_room);
+                    }
+                    held_workshops += workshop_room;
+                    capacity += f64::from(room_in_timeslot.capacity(topic)) * workshop_room;
+                    rooms_of_topic.push((room_in_timeslot, workshop_room));
+                }
+
+                if let Some(fixed_count) = fixed_count {
+                    // every fixed workshop needs a room
+                    hard_constraints.add(
+                        &mut program,
+                        HardConstraint::FixedWorkshops {
+                            timeslot: timeslot.to_owned(),
+                            topic: topic.identifier.clone(),
+                        },
+                        held_workshops,
+                        Sense::Equal,
+                        f64::from(fixed_count),
+                    );
+                } else if rooms_of_topic.is_empty() {
+                    continue;
+                } else {
+                    *repetitions_of_topic
+                        .entry(topic.identifier.as_str())
+                        .or_default() += held_workshops;
+                }
+
+                // Participant <-> Workshop (per timeslot)
+                let mut participants_of_topic = Vec::new();
+                let mut attendance = Expression::default();
+                for (participant, rank) in preferences_by_topic
+                    .get(topic.identifier.as_str())
+                    .into_iter()
+                    .flatten()
+                    .filter(|(participant, _)| {
+                        spanned.iter().all(|spanned_timeslot| {
+                            participant.is_present(&spanned_timeslot.identifier)
+                        })
+                    })
+                {
+                    let assignment = program.add_variable(
+                        timeslot.to_owned()
+                            + "_"
+                            + &topic.identifier
+                            + "_"
+                            + &participant.identifier,
+                        Domain::Binary,
+                    );
+                    attendance += assignment;
+                    for spanned_timeslot in spanned {
+                        workshops_of_participant
+                            .entry((
+                                spanned_timeslot.identifier.as_str(),
+                                participant.identifier.as_str(),
+                            ))
+                            .or_default()
+                            .push((*rank, assignment));
+                    }
+                    topics_of_participant
+                        .entry((participant.identifier.as_str(), topic.identifier.as_str()))
+                        .or_default()
+                        .push((*rank, assignment));
+                    // maximizing WorkshopTopic fullfilled times rank, once for every timeslot it takes
+                    program.objective +=
+                        f64::from(rank.0) * f64::from(topic.duration.0) * assignment;
+                    participants_of_topic.push((*participant, assignment));
+                }
+                program.add_constraint(leq(attendance, capacity));
+                let units = seat_units(
+                    &mut program,
+                    &mut hard_constraints,
+                    &hard_together_groups,
+                    topic,
+                    &rooms_of_topic,
+                    &participants_of_topic,
+                );
+
+                topics_in_timeslots.push(TopicInTimeslot {
+                    topic,
+                    timeslots: spanned,
+                    rooms: rooms_of_topic,
+                    participants: participants_of_topic,
+                    units,
+                });
+            }
+        }
+
+        // a room can only be used by one workshop at a time
+        for ((timeslot, room), topics_of_room) in topics_of_room {
+            hard_constraints.add(
+                &mut program,
+                HardConstraint::RoomOccupancy {
+                    timeslot: timeslot.to_owned(),
+                    room: room.to_owned(),
+                },
+                topics_of_room.into_iter().sum(),
+                Sense::LessOrEqual,
+                1.0,
+            );
+        }
+
+        // every participant attends at most one workshop per timeslot they are present in,
+        // exactly one if free slots are forbidden
+        for timeslot in timeslots.keys().copied() {
+            for participant in participants
+                .values()
+                .filter(|participant| participant.is_present(timeslot))
+            {
+                let workshops_of_participant = workshops_of_participant
+                    .remove(&(timeslot, participant.identifier.as_str()))
+                    .unwrap_or_default();
+                let attended: Expression = workshops_of_participant
+                    .iter()
+                    .map(|(_, assignment)| assignment)
+                    .sum();
+                match problem.free_slots {
+                    FreeSlots::Allowed if workshops_of_participant.is_empty() => continue,
+                    FreeSlots::Allowed => program.add_constraint(leq(attended, 1)),
+                    FreeSlots::Forbidden => hard_constraints.add(
+                        &mut program,
+                        HardConstraint::Attendance {
+                            timeslot: timeslot.to_owned(),
+                            participant: participant.identifier.clone(),
+                        },
+                        attended,
+                        Sense::Equal,
+                        1.0,
+                    ),
+                }
+                if !workshops_of_participant.is_empty() {
+                    participant_slots.push(workshops_of_participant);
+                }
+            }
+        }
+
+        for topic in topics.values() {
+            if let Some(repetitions) = topic.repetitions {
+                let held_workshops = repetitions_of_topic
+                    .remove(topic.identifier.as_str())
+                    .unwrap_or_default();
+                let hard_constraint = HardConstraint::Repetitions {
+                    topic: topic.identifier.clone(),
+                };
+                hard_constraints.add(
+                    &mut program,
+                    hard_constraint.clone(),
+                    held_workshops.clone(),
+                    Sense::GreaterOrEqual,
+                    f64::from(repetitions.min),
+                );
+                hard_constraints.add(
+                    &mut program,
+                    hard_constraint,
+                    held_workshops,
+                    Sense::LessOrEqual,
+                    f64::from(repetitions.max),
+                );
+            }
+        }
+
+        let buckets: Vec<BTreeMap<&str, Variable>> = topics_in_timeslots
+            .iter()
+            .map(|topic_in_timeslot: &TopicInTimeslot| {
+                topic_in_timeslot
+                    .participants
+                    .iter()
+                    .map(|(participant, assignment)| (participant.identifier.as_str(), *assignment))
+                    .collect()
+            })
+            .collect();
+        constrain_groups(
+            &problem.groups,
+            |participant| participants.contains_key(participant),
+            &buckets,
+            &mut program,
+            &mut hard_constraints,
+        )?;
+
+        let attributes = participants
+            .iter()
+            .map(|(identifier, participant)| (*identifier, &participant.attributes))
+            .collect();
+        let quota_buckets: Vec<QuotaBucket<Expression>> = topics_in_timeslots
+            .iter()
+            .map(|topic_in_timeslot| QuotaBucket {
+                target: topic_in_timeslot.topic.identifier.as_str(),
+                timeslot: Some(topic_in_timeslot.timeslots[0].identifier.as_str()),
+                held: topic_in_timeslot
+                    .rooms
+                    .iter()
+                    .map(|(_, workshop_room)| workshop_room)
+                    .sum(),
+                participants: topic_in_timeslot
+                    .participants
+                    .iter()
+                    .map(|(participant, assignment)| {
+                        (participant.identifier.as_str(), (*assignment).into())
+                    })
+                    .collect(),
+            })
+            .collect();
+        let terms = quota_terms(
+            &problem.quotas,
+            &attributes,
+            &quota_buckets,
+            |topic| topics.contains_key(topic),
+            OptimizerError::UnknownTopic,
+        )?;
+        constrain_quotas(terms, &mut program, &mut hard_constraints);
+
+        // every participant attends a topic at most once, even if it is held in multiple timeslots
+        for assignments in topics_of_participant.values() {
+            if assignments.len() > 1 {
+                let attended: Expression =
+                    assignments.iter().map(|(_, assignment)| assignment).sum();
+                program.add_constraint(leq(attended, 1));
+            }
+        }
+
+        // the assignments to the n best ranked topics of participants present in n timeslots
+        let mut top_preferences = Expression::default();
+        for (participant, topics_of_participant) in group_pairs(
+            topics_of_participant
+                .iter()
+                .map(|((participant, _), assignments)| (*participant, assignments)),
+        ) {
+            let present = timeslots
+                .keys()
+                .filter(|timeslot| participants[participant].is_present(timeslot))
+                .count();
+            let ranks: Vec<Rank> = topics_of_participant
+                .iter()
+                .filter_map(|assignments| assignments.iter().map(|(rank, _)| *rank).max())
+                .sorted()
+                .rev()
+                .collect();
+            // topics ranked as well as the worst top preference are top preferences as well
+            let Some(worst_top_rank) = ranks
+                .get(present.saturating_sub(1))
+                .or_else(|| ranks.last())
+            else {
+                continue;
+            };
+            top_preferences += topics_of_participant
+                .into_iter()
+                .flatten()
+                .filter(|(rank, _)| rank >= worst_top_rank)
+                .map(|(_, assignment)| assignment)
+                .sum::<Expression>();
+        }
+
+        if matches!(enforcement, Enforcement::Elastic) {
+            program.objective = -hard_constraints
+                .slacks
+                .iter()
+                .map(|(_, slack)| *slack)
+                .sum::<Expression>();
+        }
+
+        Ok(Self {
+            problem,
+            program,
+            topics_in_timeslots,
+            participant_slots,
+            top_preferences,
+            groups: &problem.groups,
+            hard_constraints: hard_constraints.added,
+            slacks: hard_constraints.slacks,
+        })
+    }
+
+    fn with_solution(self, solved: Solved) -> SolvedWorkshopModel<'a> {
+        SolvedWorkshopModel {
+            solution: solved.solution,
+            objective: solved.objective,
+            gap: solved.gap,
+            topics_in_timeslots: self.topics_in_timeslots,
+            groups: self.groups,
+            hard_constraints: self.hard_constraints,
+            slacks: self.slacks,
+        }
+    }
+
+    pub fn solve(mut self, runs: &SolverRuns) -> Result<SolvedWorkshopModel<'a>, OptimizerError> {
+        let solved = maximise(core::mem::take(&mut self.program), runs)?;
+        Ok(self.with_solution(solved))
+    }
+
+    pub fn write_lp(&self, writer: impl Write) -> Result<(), OptimizerError> {
+        self.program.write_lp(writer)
+    }
+
+    pub fn write_mps(&self, writer: impl Write) -> Result<(), OptimizerError> {
+        self.program.write_mps(writer)
+    }
+
+    /// Reads the solution of an external solver for the model written by
+    /// [`Self::write_lp`] or [`Self::write_mps`].
+    pub fn read_solution(
+        mut self,
+        reader: impl BufRead,
+    ) -> Result<SolvedWorkshopModel<'a>, OptimizerError> {
+        let solved = core::mem::take(&mut self.program).read_solution(reader)?;
+        Ok(self.with_solution(solved))
+    }
+}
+
+impl SolvedWorkshopModel<'_> {
+    pub fn allocation(&self) -> Allocation {
+        let mut workshops = Vec::new();
+        let mut assignments = Vec::new();
+        for topic_in_timeslot in &self.topics_in_timeslots {
+            let held_rooms: Vec<&RoomInTimeSlot> = topic_in_timeslot
+                .rooms
+                .iter()
+                .filter(|(_, workshop_room)| self.solution.value(*workshop_room) > 0.5)
+                .map(|(room_in_timeslot, _)| room_in_timeslot)
+                .collect();
+            for timeslot in topic_in_timeslot.timeslots {
+                workshops.extend(held_rooms.iter().map(|room_in_timeslot| ScheduledWorkshop {
+                    topic: topic_in_timeslot.topic.identifier.clone(),
+                    timeslot: timeslot.identifier.clone(),
+                    room: room_in_timeslot.room.identifier.clone(),
+                }));
+            }
+
+            // distribute the participants over the rooms, the capacity constraint ensures they fit,
+            // the units of hard together groups are seated in the rooms chosen by the model first
+            let mut rooms: Vec<(&Room, usize)> = topic_in_timeslot
+                .rooms
+                .iter()
+                .map(|(room_in_timeslot, workshop_room)| {
+                    let held = self.solution.value(*workshop_room) > 0.5;
+                    let seats = room_in_timeslot.capacity(topic_in_timeslot.topic);
+                    (room_in_timeslot.room, if held { seats.into() } else { 0 })
+                })
+                .collect();
+            let mut attending: BTreeSet<&str> = topic_in_timeslot
+                .participants
+                .iter()
+                .filter(|(_, assignment)| self.solution.value(*assignment) > 0.5)
+                .map(|(participant, _)| participant.identifier.as_str())
+                .collect();
+            let mut seated = Vec::new();
+            for (unit, in_rooms) in &topic_in_timeslot.units {
+                let Some(index) = in_rooms
+                    .iter()
+                    .position(|in_room| self.solution.value(*in_room) > 0.5)
+                else {
+                    continue;
+                };
+                for participant in unit {
+                    if attending.remove(participant) {
+                        rooms[index].1 = rooms[index].1.saturating_sub(1);
+                        seated.push((*participant, rooms[index].0));
+                    }
+                }
+            }
+            seated.extend(seat_participants(attending, self.groups, &rooms));
+            seated.sort_by_key(|(participant, _)| *participant);
+            for (participant, room) in seated {
+                assignments.extend(
+                    topic_in_timeslot
+                        .timeslots
+                        .iter()
+                        .map(|timeslot| Assignment {
+                            participant: participant.to_owned(),
+                            topic: topic_in_timeslot.topic.identifier.clone(),
+                            timeslot: timeslot.identifier.clone(),
+                            room: room.identifier.clone(),
+                        }),
+                );
+            }
+        }
+
+        Allocation {
+            workshops,
+            assignments,
+            objective: self.objective,
+            gap: self.gap,
+            lottery: None,
+        }
+    }
+
+    /// all [`HardConstraint`]s of the model, whether they are enforced or not
+    pub fn hard_constraints(&self) -> BTreeSet<HardConstraint> {
+        self.hard_constraints.iter().cloned().collect()
+    }
+
+    /// the [`HardConstraint`]s whose slack variables are used, only meaningful for [`Enforcement::Elastic`]
+    pub fn violated_constraints(&self) -> Vec<HardConstraint> {
+        self.slacks
+            .iter()
+            .filter(|(_, slack)| self.solution.value(*slack) > 1e-6)
+            .map(|(hard_constraint, _)| hard_constraint.clone())
+            .dedup()
+            .collect()
+    }
+}
+
+impl<'a> RankedModel<'a> for WorkshopModel<'a> {
+    type Problem = AllocationProblem;
+    type Solved = SolvedWorkshopModel<'a>;
+    type Allocation = Allocation;
+
+    fn build(problem: &'a AllocationProblem) -> Result<Self, OptimizerError> {
+        Self::new(problem, &Enforcement::All)
+    }
+
+    fn participant_slots(&self) -> &ParticipantSlots {
+        &self.participant_slots
+    }
+
+    fn program(&mut self) -> &mut LinearProgram {
+        &mut self.program
+    }
+
+    /// A participant that stays in a workshop of the same topic starting in the same timeslot
+    /// is not moved, even if it is held in another room.
+    fn kept_assignments(&self, previous: &Allocation) -> Expression {
+        let previous: BTreeSet<(&str, &str, &str)> = previous
+            .assignments
+            .iter()
+            .map(|assignment| {
+                (
+                    assignment.participant.as_str(),
+                    assignment.topic.as_str(),
+                    assignment.timeslot.as_str(),
+                )
+            })
+            .collect();
+        self.topics_in_timeslots
+            .iter()
+            .flat_map(|topic_in_timeslot| {
+                topic_in_timeslot
+                    .participants
+                    .iter()
+                    .filter(|(participant, _)| {
+                        previous.contains(&(
+                            participant.identifier.as_str(),
+                            topic_in_timeslot.topic.identifier.as_str(),
+                            topic_in_timeslot.timeslots[0].identifier.as_str(),
+                        ))
+                    })
+                    .map(|(_, assignment)| *assignment)
+            })
+            .sum()
+    }
+
+    fn top_preferences(&self) -> Expression {
+        self.top_preferences.clone()
+    }
+
+    fn initial_solution(&self, allocation: &Allocation) -> Vec<(Variable, f64)> {
+        let workshops = workshop_starts(self.problem, allocation);
+        let assignments: BTreeSet<(&str, &str, &str)> = allocation
+            .assignments
+            .iter()
+            .filter(|assignment| {
+                workshops.contains(&(
+                    assignment.topic.as_str(),
+                    assignment.timeslot.as_str(),
+                    assignment.room.as_str(),
+                ))
+            })
+            .map(|assignment| {
+                (
+                    assignment.participant.as_str(),
+                    assignment.topic.as_str(),
+                    assignment.timeslot.as_str(),
+                )
+            })
+            .collect();
+        let value = |held: bool| if held { 1.0 } else { 0.0 };
+        self.topics_in_timeslots
+            .iter()
+            .flat_map(|topic_in_timeslot| {
+                let topic = topic_in_timeslot.topic.identifier.as_str();
+                let timeslot = topic_in_timeslot.timeslots[0].identifier.as_str();
+                let rooms = topic_in_timeslot.rooms.iter().map(|(room, workshop_room)| {
+                    let held = workshops.contains(&(topic, timeslot, &room.room.identifier));
+                    (*workshop_room, value(held))
+                });
+                let participants =
+                    topic_in_timeslot
+                        .participants
+                        .iter()
+                        .map(|(participant, assignment)| {
+                            let attends = assignments.contains(&(
+                                participant.identifier.as_str(),
+                                topic,
+                                timeslot,
+                            ));
+                            (*assignment, value(attends))
+                        });
+                rooms.chain(participants).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn solve(self, runs: &SolverRuns) -> Result<SolvedWorkshopModel<'a>, OptimizerError> {
+        self.solve(runs)
+    }
+}
+
+impl SolvedModel for SolvedWorkshopModel<'_> {
+    fn objective(&self) -> f64 {
+        self.objective
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use crate::{
+        resolve, Allocation, AllocationProblem, Assignment, Participant, Preference, Rank,
+        Repetitions, Room, RoomSize, ScheduledWorkshop, SolverOptions, Timeslot, WorkshopDuration,
+        WorkshopTopic, WorkshopTopicSize,
+    };
+
+    #[test]
+    fn resolve_keeps_participant_whose_room_is_gone() {
+        let problem = AllocationProblem {
+            timeslots: vec![Timeslot {
+                identifier: "morning".to_owned(),
+            }],
+            rooms: ["a", "c"]
+                .map(|identifier| Room {
+                    identifier: identifier.to_owned(),
+                    requirements: vec![],
+                    max_size: RoomSize(5),
+                })
+                .into(),
+            topics: ["x", "y"]
+                .map(|identifier| WorkshopTopic {
+                    identifier: identifier.to_owned(),
+                    requirements: vec![],
+                    max_size: WorkshopTopicSize(5),
+                    repetitions: Some(Repetitions { min: 0, max: 1 }),
+                    duration: WorkshopDuration(1),
+                })
+                .into(),
+            participants: vec![Participant {
+                identifier: "anna".to_owned(),
+                timeslots: None,
+                attributes: BTreeMap::new(),
+            }],
+            preferences: [("x", 1), ("y", 2)]
+                .map(|(topic, rank)| Preference {
+                    participant: "anna".to_owned(),
+                    topic: topic.to_owned(),
+                    rank: Rank(rank),
+                })
+                .into(),
+            ..AllocationProblem::default()
+        };
+        // room b isn't available anymore
+        let previous = Allocation {
+            workshops: vec![ScheduledWorkshop {
+                topic: "x".to_owned(),
+                timeslot: "morning".to_owned(),
+                room: "b".to_owned(),
+            }],
+            assignments: vec![Assignment {
+                participant: "anna".to_owned(),
+                topic: "x".to_owned(),
+                timeslot: "morning".to_owned(),
+                room: "b".to_owned(),
+            }],
+            objective: 1.0,
+            gap: None,
+            lottery: None,
+        };
+        let allocation = resolve(&problem, &previous, &SolverOptions::default()).unwrap();
+
+        assert_eq!(allocation.assignments.len(), 1);
+        assert_eq!(allocation.assignments[0].topic, "x");
+        assert_ne!(allocation.assignments[0].room, "b");
+    }
+}
@@ -0,0 +1,103 @@
+use alloc::collections::VecDeque;
+
+struct Edge {
+    to: usize,
+    /// the remaining capacity
+    capacity: u32,
+    cost: i64,
+    /// the opposite edge in the residual network
+    reverse: usize,
+}
+
+/// A flow network for solving transportation problems exactly without an ILP solver.
+#[derive(Default)]
+pub struct FlowNetwork {
+    edges: Vec<Edge>,
+    /// the outgoing edges of every node
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl FlowNetwork {
+    pub fn add_node(&mut self) -> usize {
+        self.adjacency.push(Vec::new());
+        self.adjacency.len() - 1
+    }
+
+    /// Adds an edge and returns its index to query its [`Self::flow`].
+    pub fn add_edge(&mut self, from: usize, to: usize, capacity: u32, cost: i64) -> usize {
+        let index = self.edges.len();
+        self.edges.push(Edge {
+            to,
+            capacity,
+            cost,
+            reverse: index + 1,
+        });
+        self.edges.push(Edge {
+            to: from,
+            capacity: 0,
+            cost: -cost,
+            reverse: index,
+        });
+        self.adjacency[from].push(index);
+        self.adjacency[to].push(index + 1);
+        index
+    }
+
+    /// the flow through an edge returned by [`Self::add_edge`]
+    pub fn flow(&self, edge: usize) -> u32 {
+        self.edges[self.edges[edge].reverse].capacity
+    }
+
+    /// The cheapest path with remaining capacity as the edges leading to every node on it,
+    /// using the Bellman-Ford algorithm as costs may be negative.
+    fn shortest_path(&self, source: usize, sink: usize) -> Option<Vec<usize>> {
+        let mut distances = vec![i64::MAX; self.adjacency.len()];
+        let mut predecessors = vec![None; self.adjacency.len()];
+        let mut queued = vec![false; self.adjacency.len()];
+        let mut queue = VecDeque::from([source]);
+        distances[source] = 0;
+        while let Some(node) = queue.pop_front() {
+            queued[node] = false;
+            for &index in &self.adjacency[node] {
+                let edge = &self.edges[index];
+                let distance = distances[node] + edge.cost;
+                if edge.capacity > 0 && distance < distances[edge.to] {
+                    distances[edge.to] = distance;
+                    predecessors[edge.to] = Some(index);
+                    if !queued[edge.to] {
+                        queued[edge.to] = true;
+                        queue.push_back(edge.to);
+                    }
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut node = sink;
+        while node != source {
+            let index = predecessors[node]?;
+            path.push(index);
+            node = self.edges[self.edges[index].reverse].to;
+        }
+        Some(path)
+    }
+
+    /// Sends as much flow as possible from `source` to `sink` at the lowest cost using
+    /// successive shortest paths, returning the amount of flow.
+    pub fn min_cost_max_flow(&mut self, source: usize, sink: usize) -> u32 {
+        let mut total = 0;
+        while let Some(path) = self.shortest_path(source, sink) {
+            let Some(bottleneck) = path.iter().map(|index| self.edges[*index].capacity).min()
+            else {
+                break;
+            };
+            for index in path {
+                self.edges[index].capacity -= bottleneck;
+                let reverse = self.edges[index].reverse;
+                self.edges[reverse].capacity += bottleneck;
+            }
+            total += bottleneck;
+        }
+        total
+    }
+}
@@ -0,0 +1,409 @@
+use alloc::collections::BTreeSet;
+
+use good_lp::{Expression, Variable};
+use tracing::info;
+
+use crate::program::{geq, leq, Domain, LinearConstraint, LinearProgram};
+use crate::solver::SolverRuns;
+use crate::{OptimizerError, Rank};
+
+/// How the ranks of the individual assignments are combined into the optimized objective.
+///
+/// An unassigned participant counts as worse than any rank for the fairness aware objectives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Objective {
+    /// maximize the summed rank of all assignments
+    #[default]
+    Utilitarian,
+    /// maximize the worst rank anyone gets, ties are broken by the summed rank
+    MaxMin,
+    /// minimize the number of participants with the worst rank, then the number
+    /// of participants with the next worst rank and so on, ties are broken by the summed rank
+    Lexicographic,
+    /// maximize the number of assignments to top preferences, for a participant present in n timeslots
+    /// these are the n best ranked topics, so the timeslots are spread over the favourites instead of
+    /// e.g. giving one participant all of them and another none. Ties are broken by the summed rank
+    Diversity,
+}
+
+/// How good an assignment in a slot is for a participant,
+/// `0` if the participant is unassigned and `rank + 1` otherwise.
+pub fn level(rank: Rank) -> u16 {
+    u16::from(rank.0) + 1
+}
+
+/// `SplitMix64`, small and stable so a lottery can be reproduced from its seed.
+pub const fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut value = *state;
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+/// Random weights for `variables` that sum up to less than a half, so adding them to an
+/// objective with integral values only decides between otherwise equally good solutions.
+pub fn random_tie_breaker(seed: u64, variables: &[Variable]) -> Expression {
+    let scale = 0.5 / (f64::from(u32::try_from(variables.len()).unwrap_or(u32::MAX)) + 1.0);
+    let mut state = seed;
+    variables
+        .iter()
+        .map(|variable| {
+            let weight = f64::from(u32::try_from(split_mix(&mut state) >> 32).unwrap_or(0))
+                / f64::from(u32::MAX);
+            weight * scale * *variable
+        })
+        .sum()
+}
+
+/// The assignment variables of every participant in every slot (e.g. timeslot) together with their rank.
+/// A participant is assigned at most once per slot.
+#[derive(Default)]
+pub struct ParticipantSlots(Vec<Vec<(Rank, Variable)>>);
+
+impl ParticipantSlots {
+    pub fn push(&mut self, slot: Vec<(Rank, Variable)>) {
+        self.0.push(slot);
+    }
+
+    /// The levels participants can reach, see [`level`].
+    pub fn levels(&self) -> BTreeSet<u16> {
+        core::iter::once(0)
+            .chain(self.0.iter().flatten().map(|(rank, _)| level(*rank)))
+            .collect()
+    }
+
+    /// The number of participant slots at the given [`level`].
+    pub fn level_count(&self, level_to_count: u16) -> Expression {
+        self.0
+            .iter()
+            .map(|slot| {
+                if level_to_count == 0 {
+                    1 - slot
+                        .iter()
+                        .map(|(_, assignment)| assignment)
+                        .sum::<Expression>()
+                } else {
+                    slot.iter()
+                        .filter(|(rank, _)| level(*rank) == level_to_count)
+                        .map(|(_, assignment)| assignment)
+                        .sum()
+                }
+            })
+            .sum()
+    }
+
+    /// The number of assignments with the best rank of their slot.
+    pub fn best_ranked(&self) -> Expression {
+        self.0
+            .iter()
+            .flat_map(|slot| {
+                let best_rank = slot.iter().map(|(rank, _)| *rank).max();
+                slot.iter()
+                    .filter(move |(rank, _)| Some(*rank) == best_rank)
+                    .map(|(_, assignment)| *assignment)
+            })
+            .sum()
+    }
+
+    /// A [`random_tie_breaker`] for all assignment variables.
+    pub fn lottery(&self, seed: u64) -> Expression {
+        let assignments: Vec<Variable> = self
+            .0
+            .iter()
+            .flatten()
+            .map(|(_, assignment)| *assignment)
+            .collect();
+        random_tie_breaker(seed, &assignments)
+    }
+
+    /// Constrains `worst_level` to be at most the [`level`] of every participant slot.
+    pub fn worst_level_constraints(&self, worst_level: Variable) -> Vec<LinearConstraint> {
+        self.0
+            .iter()
+            .map(|slot| {
+                let slot_level: Expression = slot
+                    .iter()
+                    .map(|(rank, assignment)| f64::from(level(*rank)) * *assignment)
+                    .sum();
+                geq(slot_level, worst_level)
+            })
+            .collect()
+    }
+}
+
+/// A model whose objective can be chosen by an [`Objective`].
+pub trait RankedModel<'a>: Sized {
+    type Problem: 'a;
+    type Solved: SolvedModel;
+    type Allocation;
+
+    /// Builds the model maximizing the summed rank.
+    fn build(problem: &'a Self::Problem) -> Result<Self, OptimizerError>;
+
+    fn participant_slots(&self) -> &ParticipantSlots;
+
+    fn program(&mut self) -> &mut LinearProgram;
+
+    /// The number of assignments of `previous` that are kept, assignments to participants
+    /// or workshops that no longer exist are ignored.
+    fn kept_assignments(&self, previous: &Self::Allocation) -> Expression;
+
+    /// The number of assignments to top preferences, see [`Objective::Diversity`].
+    fn top_preferences(&self) -> Expression;
+
+    /// The values of the variables for `allocation` to start the solver from,
+    /// by default the solver starts without a solution.
+    fn initial_solution(&self, _allocation: &Self::Allocation) -> Vec<(Variable, f64)> {
+        Vec::new()
+    }
+
+    fn solve(self, runs: &SolverRuns) -> Result<Self::Solved, OptimizerError>;
+}
+
+pub trait SolvedModel {
+    /// the value of the optimized objective
+    fn objective(&self) -> f64;
+}
+
+fn add_worst_level<'a, M: RankedModel<'a>>(model: &mut M) -> Variable {
+    let worst_level = model
+        .program()
+        .add_variable("worst_level", Domain::NonNegative);
+    for constraint in model
+        .participant_slots()
+        .worst_level_constraints(worst_level)
+    {
+        model.program().add_constraint(constraint);
+    }
+    worst_level
+}
+
+/// Maximizes the summed rank, the last stage of every [`Objective`].
+fn solve_summed_rank<'a, M: RankedModel<'a>>(
+    mut model: M,
+    runs: &SolverRuns,
+) -> Result<M::Solved, OptimizerError> {
+    if let Some(seed) = runs.options.lottery {
+        let lottery = model.participant_slots().lottery(seed);
+        model.program().tie_breaker += lottery;
+    }
+    model.solve(runs)
+}
+
+/// Optimizes `problem` according to `objective`, possibly solving the model multiple times.
+///
+/// If a `previous` allocation is given as many of its assignments as possible are kept,
+/// the `objective` only decides between allocations that move the fewest participants.
+/// A `warm_start` allocation, e.g. of [`crate::heuristic`], is passed to the solver as a starting
+/// solution and its summed rank prunes the search for [`Objective::Utilitarian`]. All solver `runs` share one time limit.
+pub fn optimize<'a, M: RankedModel<'a>>(
+    problem: &'a M::Problem,
+    objective: Objective,
+    previous: Option<&M::Allocation>,
+    warm_start: Option<(&M::Allocation, f64)>,
+    runs: &SolverRuns,
+) -> Result<M::Solved, OptimizerError> {
+    let most_kept = previous
+        .map(|previous| {
+            let mut model = M::build(problem)?;
+            model.program().objective = model.kept_assignments(previous);
+            let most_kept = model.solve(runs)?.objective().round();
+            info!("kept assignments {most_kept}");
+            Ok::<_, OptimizerError>((previous, most_kept))
+        })
+        .transpose()?;
+    // every following stage keeps as many assignments
+    let build = || -> Result<M, OptimizerError> {
+        let mut model = M::build(problem)?;
+        if let Some((previous, most_kept)) = most_kept {
+            let kept = model.kept_assignments(previous);
+            model.program().add_constraint(geq(kept, most_kept));
+        }
+        if let Some((allocation, _)) = warm_start {
+            model.program().initial_solution = model.initial_solution(allocation);
+        }
+        Ok(model)
+    };
+
+    match objective {
+        Objective::Utilitarian => {
+            let mut model = build()?;
+            if let Some((_, bound)) = warm_start {
+                let summed_rank = model.program().objective.clone();
+                // the margin avoids cutting off the allocation of the bound by rounding errors
+                model
+                    .program()
+                    .add_constraint(geq(summed_rank, bound - 0.5));
+            }
+            solve_summed_rank(model, runs)
+        }
+        Objective::MaxMin => {
+            let mut model = build()?;
+            let worst_level = add_worst_level(&mut model);
+            model.program().objective = worst_level.into();
+            let best_worst_level = model.solve(runs)?.objective().round();
+            info!("best worst level {best_worst_level}");
+
+            let mut model = build()?;
+            let worst_level = add_worst_level(&mut model);
+            model
+                .program()
+                .add_constraint(geq(worst_level, best_worst_level));
+            solve_summed_rank(model, runs)
+        }
+        Objective::Lexicographic => {
+            let levels = build()?.participant_slots().levels();
+            let mut fixed_counts: Vec<(u16, f64)> = Vec::new();
+            // the count of the best level follows from the others
+            for level in levels.iter().rev().skip(1).rev() {
+                let mut model = build()?;
+                for (fixed_level, count) in &fixed_counts {
+                    let level_count = model.participant_slots().level_count(*fixed_level);
+                    model.program().add_constraint(leq(level_count, *count));
+                }
+                let level_count = model.participant_slots().level_count(*level);
+                model.program().objective = -level_count;
+                let count = -model.solve(runs)?.objective().round();
+                info!("level {level} count {count}");
+                fixed_counts.push((*level, count));
+            }
+
+            let mut model = build()?;
+            for (fixed_level, count) in &fixed_counts {
+                let level_count = model.participant_slots().level_count(*fixed_level);
+                model.program().add_constraint(leq(level_count, *count));
+            }
+            solve_summed_rank(model, runs)
+        }
+        Objective::Diversity => {
+            let mut model = build()?;
+            model.program().objective = model.top_preferences();
+            let most_top_preferences = model.solve(runs)?.objective().round();
+            info!("top preferences {most_top_preferences}");
+
+            let mut model = build()?;
+            let top_preferences = model.top_preferences();
+            model
+                .program()
+                .add_constraint(geq(top_preferences, most_top_preferences));
+            solve_summed_rank(model, runs)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::{BTreeMap, BTreeSet};
+
+    use super::Objective;
+    use crate::projects::{solve, Project, ProjectParticipant, ProjectPreference, ProjectProblem};
+    use crate::{Rank, SolverOptions};
+
+    /// three participants and three projects with one place each
+    fn problem(preferences: &[(&str, &str, u8)], objective: Objective) -> ProjectProblem {
+        ProjectProblem {
+            projects: ["a", "b", "c"]
+                .map(|identifier| Project {
+                    identifier: identifier.to_owned(),
+                    min_age: 0,
+                    max_age: 20,
+                    min_participants: 0,
+                    max_participants: 1,
+                    leaders: vec![],
+                    random_assignments: false,
+                })
+                .into(),
+            participants: ["p1", "p2", "p3"]
+                .map(|identifier| ProjectParticipant {
+                    identifier: identifier.to_owned(),
+                    age: 12,
+                    attributes: BTreeMap::new(),
+                })
+                .into(),
+            preferences: preferences
+                .iter()
+                .map(|(participant, project, rank)| ProjectPreference {
+                    participant: (*participant).to_owned(),
+                    project: (*project).to_owned(),
+                    rank: Rank(*rank),
+                })
+                .collect(),
+            objective,
+            ..ProjectProblem::default()
+        }
+    }
+
+    /// the projects `p1` and `p2` attend, `p3` takes the one left
+    fn solve_projects(preferences: &[(&str, &str, u8)], objective: Objective) -> [String; 2] {
+        let allocation =
+            solve(&problem(preferences, objective), &SolverOptions::default()).unwrap();
+        ["p1", "p2"].map(|participant| {
+            allocation
+                .assignments
+                .iter()
+                .find(|assignment| assignment.participant == participant)
+                .map(|assignment| assignment.project.clone())
+                .unwrap_or_default()
+        })
+    }
+
+    #[test]
+    fn max_min_raises_the_worst_rank() {
+        let preferences = [
+            ("p1", "a", 9),
+            ("p1", "b", 3),
+            ("p2", "a", 4),
+            ("p2", "c", 2),
+            ("p3", "a", 5),
+            ("p3", "b", 5),
+            ("p3", "c", 5),
+        ];
+        assert_eq!(
+            solve_projects(&preferences, Objective::Utilitarian),
+            ["a", "c"]
+        );
+        assert_eq!(solve_projects(&preferences, Objective::MaxMin), ["b", "a"]);
+    }
+
+    #[test]
+    fn lexicographic_minimizes_the_participants_with_the_worst_rank() {
+        // `p3` gets the worst rank anyway
+        let preferences = [
+            ("p1", "a", 9),
+            ("p1", "b", 2),
+            ("p2", "a", 3),
+            ("p2", "b", 1),
+            ("p3", "a", 1),
+            ("p3", "b", 1),
+            ("p3", "c", 1),
+        ];
+        assert_eq!(solve_projects(&preferences, Objective::MaxMin), ["a", "b"]);
+        assert_eq!(
+            solve_projects(&preferences, Objective::Lexicographic),
+            ["b", "a"]
+        );
+    }
+
+    #[test]
+    fn lottery_breaks_ties_by_its_seed() {
+        let preferences: Vec<(&str, &str, u8)> = ["p1", "p2", "p3"]
+            .into_iter()
+            .flat_map(|participant| ["a", "b", "c"].map(|project| (participant, project, 1)))
+            .collect();
+        // not a transportation problem, so the lottery is part of the model
+        let problem = problem(&preferences, Objective::MaxMin);
+        let fingerprint = |seed| {
+            let options = SolverOptions {
+                lottery: Some(seed),
+                ..SolverOptions::default()
+            };
+            solve(&problem, &options).unwrap().fingerprint()
+        };
+
+        assert_eq!(fingerprint(1), fingerprint(1));
+        let fingerprints: BTreeSet<String> = (1..=8).map(fingerprint).collect();
+        assert!(fingerprints.len() > 1);
+    }
+}
@@ -0,0 +1,163 @@
+use alloc::collections::BTreeMap;
+
+use crate::{Objective, ParticipantGroup, Quota};
+
+// these will come from the database later
+// it needs to be ensured that the names are *globally* unique, so it makes sense to append an id there
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Requirement {
+    pub identifier: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timeslot {
+    pub identifier: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoomSize(pub u8);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Room {
+    pub identifier: String,
+    /// identifiers of the [`Requirement`]s this room fulfills
+    pub requirements: Vec<String>,
+    pub max_size: RoomSize,
+}
+
+/// Makes a [`Room`] available in a [`Timeslot`]. Rooms without any availability are available
+/// in every timeslot, all others only in the listed ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoomAvailability {
+    /// identifier of the [`Room`]
+    pub room: String,
+    /// identifier of the [`Timeslot`]
+    pub timeslot: String,
+    /// overrides [`Room::max_size`] in this timeslot, e.g. if only a part of the room can be used
+    pub max_size: Option<RoomSize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorkshopTopicSize(pub u8);
+
+/// How many consecutive [`Timeslot`]s a workshop takes, e.g. an excursion. It is held in the same
+/// room with the same participants in all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorkshopDuration(pub u8);
+
+impl Default for WorkshopDuration {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// How often an automatically scheduled [`WorkshopTopic`] is held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Repetitions {
+    pub min: u8,
+    pub max: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorkshopTopic {
+    pub identifier: String,
+    /// identifiers of the [`Requirement`]s a room needs to fulfill for this topic
+    pub requirements: Vec<String>,
+    pub max_size: WorkshopTopicSize,
+    /// If set the optimizer decides in which timeslots and rooms this topic is held,
+    /// otherwise it is held as listed in [`AllocationProblem::workshops`].
+    pub repetitions: Option<Repetitions>,
+    /// the workshops of this topic take the timeslot they start in and the following ones
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub duration: WorkshopDuration,
+}
+
+// in theory if a person holds multiple workshops the system could decide which one should be held how many times.
+// topics with `repetitions` are scheduled like that, for all others the workshops need to be listed explicitly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Workshop {
+    /// identifier of the [`WorkshopTopic`]
+    pub topic: String,
+    /// identifier of the [`Timeslot`] the workshop starts in
+    pub timeslot: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Participant {
+    pub identifier: String,
+    /// identifiers of the [`Timeslot`]s the participant is present in, all of them if `None`
+    pub timeslots: Option<Vec<String>>,
+    /// e.g. the class or grade, see [`Quota`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl Participant {
+    #[must_use]
+    pub fn is_present(&self, timeslot: &str) -> bool {
+        self.timeslots
+            .as_ref()
+            .is_none_or(|timeslots| timeslots.iter().any(|present| present == timeslot))
+    }
+}
+
+/// Higher ranks are preferred, see [`Objective`] for how they are optimized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rank(pub u8);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Preference {
+    /// identifier of the [`Participant`]
+    pub participant: String,
+    /// identifier of the [`WorkshopTopic`]
+    pub topic: String,
+    pub rank: Rank,
+}
+
+/// Whether participants have to attend a workshop in every [`Timeslot`] they are present in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FreeSlots {
+    /// participants attend at most one workshop per timeslot and are listed in
+    /// [`crate::AllocationReport::unassigned`] otherwise
+    #[default]
+    Allowed,
+    /// participants attend exactly one workshop in every timeslot they are present in,
+    /// the problem is infeasible if that isn't possible
+    Forbidden,
+}
+
+/// All input data of a single allocation run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AllocationProblem {
+    pub requirements: Vec<Requirement>,
+    /// in chronological order, see [`WorkshopTopic::duration`]
+    pub timeslots: Vec<Timeslot>,
+    pub rooms: Vec<Room>,
+    pub room_availability: Vec<RoomAvailability>,
+    pub topics: Vec<WorkshopTopic>,
+    pub workshops: Vec<Workshop>,
+    pub participants: Vec<Participant>,
+    pub preferences: Vec<Preference>,
+    pub groups: Vec<ParticipantGroup>,
+    /// apply to all workshops of a topic starting in the same timeslot together
+    pub quotas: Vec<Quota>,
+    pub objective: Objective,
+    pub free_slots: FreeSlots,
+}
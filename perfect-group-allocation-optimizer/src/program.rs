@@ -0,0 +1,447 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use good_lp::solvers::SolutionStatus;
+use good_lp::{
+    constraint, variable, Constraint, Expression, IntoAffineExpression, ProblemVariables, Solution,
+    Variable,
+};
+
+use crate::solver::Solved;
+use crate::OptimizerError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sense {
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+}
+
+/// `expression sense 0`, unlike a [`Constraint`] it can be inspected to export the model.
+#[derive(Debug, Clone)]
+pub struct LinearConstraint {
+    expression: Expression,
+    sense: Sense,
+}
+
+pub fn leq(lhs: impl IntoAffineExpression, rhs: impl IntoAffineExpression) -> LinearConstraint {
+    LinearConstraint {
+        expression: Expression::from_other_affine(lhs) - Expression::from_other_affine(rhs),
+        sense: Sense::LessOrEqual,
+    }
+}
+
+pub fn eq(lhs: impl IntoAffineExpression, rhs: impl IntoAffineExpression) -> LinearConstraint {
+    LinearConstraint {
+        expression: Expression::from_other_affine(lhs) - Expression::from_other_affine(rhs),
+        sense: Sense::Equal,
+    }
+}
+
+pub fn geq(lhs: impl IntoAffineExpression, rhs: impl IntoAffineExpression) -> LinearConstraint {
+    LinearConstraint {
+        expression: Expression::from_other_affine(lhs) - Expression::from_other_affine(rhs),
+        sense: Sense::GreaterOrEqual,
+    }
+}
+
+impl LinearConstraint {
+    pub fn new(expression: Expression, sense: Sense, bound: f64) -> Self {
+        Self {
+            expression: expression - bound,
+            sense,
+        }
+    }
+
+    pub fn to_constraint(&self) -> Constraint {
+        let expression = self.expression.clone();
+        match self.sense {
+            Sense::LessOrEqual => constraint!(expression <= 0),
+            Sense::Equal => constraint!(expression == 0),
+            Sense::GreaterOrEqual => constraint!(expression >= 0),
+        }
+    }
+
+    /// the constant right hand side if all variables are on the left
+    fn bound(&self) -> f64 {
+        // avoids writing `-0`
+        0.0 - self.expression.constant()
+    }
+
+    fn is_satisfied(&self, solution: &dyn Solution) -> bool {
+        let expression = &self.expression;
+        let value = expression.constant()
+            + expression
+                .linear_coefficients()
+                .map(|(variable, coefficient)| coefficient * solution.value(variable))
+                .sum::<f64>();
+        match self.sense {
+            Sense::LessOrEqual => value <= FEASIBILITY_TOLERANCE,
+            Sense::Equal => value.abs() <= FEASIBILITY_TOLERANCE,
+            Sense::GreaterOrEqual => value >= -FEASIBILITY_TOLERANCE,
+        }
+    }
+}
+
+/// how much a solution found by a solver may violate the constraints
+const FEASIBILITY_TOLERANCE: f64 = 1e-5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Domain {
+    Binary,
+    NonNegative,
+    /// a non-negative integer
+    Integer,
+}
+
+/// The variables, constraints and objective of a model that is maximized.
+#[derive(Default)]
+pub struct LinearProgram {
+    variables: ProblemVariables,
+    /// in the order they were added
+    definitions: Vec<(Variable, String, Domain)>,
+    constraints: Vec<LinearConstraint>,
+    pub objective: Expression,
+    /// added to the objective while solving to decide between equally good solutions,
+    /// but not part of the reported objective value. As the objective values are integral
+    /// it has to vary by less than one, every user of it gets a budget of a half.
+    pub tie_breaker: Expression,
+    /// values of some variables the solver may start from, it ignores them if they are infeasible
+    pub initial_solution: Vec<(Variable, f64)>,
+}
+
+/// Variable values that weren't returned by `good_lp`, e.g. read from a file written by an external solver.
+struct VariableValues(HashMap<Variable, f64>);
+
+impl Solution for VariableValues {
+    /// like `good_lp` for a `HashMap`, the status of these values is unknown
+    fn status(&self) -> SolutionStatus {
+        SolutionStatus::Optimal
+    }
+
+    fn value(&self, variable: Variable) -> f64 {
+        self.0.get(&variable).copied().unwrap_or_default()
+    }
+}
+
+fn is_valid_name_character(character: char) -> bool {
+    character.is_ascii_alphanumeric() || "!\"#$%&()/,.;?@_`'{}|~".contains(character)
+}
+
+impl LinearProgram {
+    pub fn add_variable(&mut self, name: impl Into<String>, domain: Domain) -> Variable {
+        let name = name.into();
+        let definition = match domain {
+            Domain::Binary => variable().binary(),
+            Domain::NonNegative => variable().min(0),
+            Domain::Integer => variable().integer().min(0),
+        };
+        let variable = self.variables.add(definition.name(name.clone()));
+        self.definitions.push((variable, name, domain));
+        variable
+    }
+
+    pub fn add_constraint(&mut self, constraint: LinearConstraint) {
+        self.constraints.push(constraint);
+    }
+
+    /// Moves the variables out to pass them to a solver, afterwards no variables can be added.
+    pub fn take_variables(&mut self) -> ProblemVariables {
+        core::mem::take(&mut self.variables)
+    }
+
+    pub fn constraints(&self) -> impl Iterator<Item = Constraint> + '_ {
+        self.constraints.iter().map(LinearConstraint::to_constraint)
+    }
+
+    /// The objective including the tie breaker.
+    pub fn solved_objective(&self) -> Expression {
+        self.objective.clone() + self.tie_breaker.clone()
+    }
+
+    /// A solution with the `values` of the variables in the order they were added,
+    /// which is the column order of the solvers.
+    #[cfg(feature = "coin_cbc")]
+    pub fn column_solution(&self, values: &[f64]) -> impl Solution + 'static {
+        VariableValues(
+            self.definitions
+                .iter()
+                .map(|(variable, _, _)| *variable)
+                .zip(values.iter().copied())
+                .collect(),
+        )
+    }
+
+    /// Whether `solution` satisfies all constraints and domains, solutions of solvers
+    /// that were stopped early may not.
+    pub fn is_feasible(&self, solution: &dyn Solution) -> bool {
+        self.definitions.iter().all(|(variable, _, domain)| {
+            let value = solution.value(*variable);
+            match domain {
+                Domain::Binary => {
+                    value.abs() <= FEASIBILITY_TOLERANCE
+                        || (value - 1.0).abs() <= FEASIBILITY_TOLERANCE
+                }
+                Domain::NonNegative => value >= -FEASIBILITY_TOLERANCE,
+                Domain::Integer => {
+                    value >= -FEASIBILITY_TOLERANCE
+                        && (value - value.round()).abs() <= FEASIBILITY_TOLERANCE
+                }
+            }
+        }) && self
+            .constraints
+            .iter()
+            .all(|constraint| constraint.is_satisfied(solution))
+    }
+
+    /// Unique names that are valid in LP and MPS files, based on the variable names.
+    fn exported_names(&self) -> Vec<String> {
+        let mut used = HashMap::new();
+        self.definitions
+            .iter()
+            .enumerate()
+            .map(|(index, (_, name, _))| {
+                let mut exported: String = name
+                    .chars()
+                    .map(|character| {
+                        if is_valid_name_character(character) {
+                            character
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect();
+                if !exported.starts_with(|character: char| character.is_ascii_alphabetic()) {
+                    exported.insert(0, 'x');
+                }
+                if used.insert(exported.clone(), index).is_some() {
+                    exported = format!("{exported}_{index}");
+                    used.insert(exported.clone(), index);
+                }
+                exported
+            })
+            .collect()
+    }
+
+    /// The coefficients of `expression` in the order the variables were added.
+    fn coefficients(&self, expression: &Expression) -> Vec<(usize, f64)> {
+        let positions: HashMap<Variable, usize> = self
+            .definitions
+            .iter()
+            .enumerate()
+            .map(|(index, (variable, _, _))| (*variable, index))
+            .collect();
+        let mut coefficients: Vec<(usize, f64)> = expression
+            .linear_coefficients()
+            .filter(|(_, coefficient)| *coefficient != 0.0)
+            .map(|(variable, coefficient)| (positions[&variable], coefficient))
+            .collect();
+        coefficients.sort_unstable_by_key(|(position, _)| *position);
+        coefficients
+    }
+
+    /// Writes the program in the CPLEX LP format.
+    pub fn write_lp(&self, mut writer: impl Write) -> Result<(), OptimizerError> {
+        let names = self.exported_names();
+        let objective = self.objective.clone() + self.tie_breaker.clone();
+        let write_terms =
+            |writer: &mut dyn Write, coefficients: &[(usize, f64)]| -> std::io::Result<()> {
+                if coefficients.is_empty() {
+                    // a row needs at least one variable
+                    if let Some(first) = names.first() {
+                        write!(writer, " 0 {first}")?;
+                    }
+                }
+                for (index, (position, coefficient)) in coefficients.iter().enumerate() {
+                    if index % 8 == 7 {
+                        // CPLEX limits the line length
+                        write!(writer, "\n   ")?;
+                    }
+                    let sign = if coefficient.is_sign_negative() {
+                        '-'
+                    } else {
+                        '+'
+                    };
+                    write!(writer, " {sign} {} {}", coefficient.abs(), names[*position])?;
+                }
+                Ok(())
+            };
+
+        writeln!(writer, "\\ perfect-group-allocation")?;
+        if objective.constant() != 0.0 {
+            writeln!(writer, "\\ objective constant {}", objective.constant())?;
+        }
+        writeln!(writer, "Maximize")?;
+        write!(writer, " obj:")?;
+        write_terms(&mut writer, &self.coefficients(&objective))?;
+        writeln!(writer)?;
+
+        writeln!(writer, "Subject To")?;
+        for (index, constraint) in self.constraints.iter().enumerate() {
+            write!(writer, " c{index}:")?;
+            write_terms(&mut writer, &self.coefficients(&constraint.expression))?;
+            let sense = match constraint.sense {
+                Sense::LessOrEqual => "<=",
+                Sense::Equal => "=",
+                Sense::GreaterOrEqual => ">=",
+            };
+            writeln!(writer, " {sense} {}", constraint.bound())?;
+        }
+
+        writeln!(writer, "Binaries")?;
+        for ((_, _, domain), name) in self.definitions.iter().zip(&names) {
+            if *domain == Domain::Binary {
+                writeln!(writer, " {name}")?;
+            }
+        }
+        writeln!(writer, "Generals")?;
+        for ((_, _, domain), name) in self.definitions.iter().zip(&names) {
+            if *domain == Domain::Integer {
+                writeln!(writer, " {name}")?;
+            }
+        }
+        writeln!(writer, "End")?;
+        Ok(())
+    }
+
+    /// Writes the program in the free MPS format.
+    pub fn write_mps(&self, mut writer: impl Write) -> Result<(), OptimizerError> {
+        let names = self.exported_names();
+        let objective = self.objective.clone() + self.tie_breaker.clone();
+
+        // the rows every variable is part of
+        let mut columns: Vec<Vec<(String, f64)>> = vec![Vec::new(); names.len()];
+        for (position, coefficient) in self.coefficients(&objective) {
+            columns[position].push(("obj".to_owned(), coefficient));
+        }
+        for (index, constraint) in self.constraints.iter().enumerate() {
+            for (position, coefficient) in self.coefficients(&constraint.expression) {
+                columns[position].push((format!("c{index}"), coefficient));
+            }
+        }
+
+        writeln!(writer, "* perfect-group-allocation")?;
+        if objective.constant() != 0.0 {
+            writeln!(writer, "* objective constant {}", objective.constant())?;
+        }
+        writeln!(writer, "NAME perfect-group-allocation")?;
+        writeln!(writer, "OBJSENSE")?;
+        writeln!(writer, "    MAX")?;
+        writeln!(writer, "ROWS")?;
+        writeln!(writer, " N obj")?;
+        for (index, constraint) in self.constraints.iter().enumerate() {
+            let sense = match constraint.sense {
+                Sense::LessOrEqual => 'L',
+                Sense::Equal => 'E',
+                Sense::GreaterOrEqual => 'G',
+            };
+            writeln!(writer, " {sense} c{index}")?;
+        }
+
+        writeln!(writer, "COLUMNS")?;
+        for (column, name) in columns.iter().zip(&names) {
+            if column.is_empty() {
+                writeln!(writer, "    {name} obj 0")?;
+            }
+            for (row, coefficient) in column {
+                writeln!(writer, "    {name} {row} {coefficient}")?;
+            }
+        }
+
+        writeln!(writer, "RHS")?;
+        for (index, constraint) in self.constraints.iter().enumerate() {
+            if constraint.bound() != 0.0 {
+                writeln!(writer, "    RHS c{index} {}", constraint.bound())?;
+            }
+        }
+
+        writeln!(writer, "BOUNDS")?;
+        for ((_, _, domain), name) in self.definitions.iter().zip(&names) {
+            match domain {
+                Domain::Binary => writeln!(writer, " BV BND {name}")?,
+                Domain::NonNegative => {}
+                Domain::Integer => writeln!(writer, " LI BND {name} 0")?,
+            }
+        }
+        writeln!(writer, "ENDATA")?;
+        Ok(())
+    }
+
+    /// Reads the variable values written by an external solver for an exported program.
+    ///
+    /// Every line contains a variable name followed by its value, possibly preceded by
+    /// a column number like in Cbc solution files. Lines that don't match are skipped,
+    /// only the first value of a variable is used and missing variables are zero.
+    pub fn read_solution(self, reader: impl BufRead) -> Result<Solved, OptimizerError> {
+        let variables: HashMap<String, Variable> = self
+            .exported_names()
+            .into_iter()
+            .zip(self.definitions.iter().map(|(variable, _, _)| *variable))
+            .collect();
+
+        let mut values = HashMap::new();
+        for line in reader.lines() {
+            let line = line?;
+            let mut tokens = line.split_whitespace().peekable();
+            // exported names never start with a digit
+            if tokens
+                .peek()
+                .is_some_and(|token| token.parse::<usize>().is_ok())
+            {
+                tokens.next();
+            }
+            let (Some(name), Some(Ok(value))) = (tokens.next(), tokens.next().map(str::parse))
+            else {
+                continue;
+            };
+            if let Some(variable) = variables.get(name) {
+                // later sections may contain e.g. dual values
+                values.entry(*variable).or_insert(value);
+            }
+        }
+        if values.is_empty() && !variables.is_empty() {
+            return Err(OptimizerError::InvalidSolution);
+        }
+
+        let solution = VariableValues(values);
+        Ok(Solved {
+            objective: solution.eval(&self.objective),
+            // the solution file doesn't say whether it is optimal
+            gap: None,
+            solution: Box::new(solution),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{leq, Domain, LinearProgram};
+
+    #[test]
+    fn exports_program_and_reads_solution() {
+        let mut program = LinearProgram::default();
+        let room = program.add_variable("room 1", Domain::Binary);
+        let count = program.add_variable("2nd", Domain::Integer);
+        program.add_constraint(leq(room + count, 3));
+        program.objective = 2 * room + count;
+
+        let mut lp = Vec::new();
+        program.write_lp(&mut lp).unwrap();
+        let lp = String::from_utf8(lp).unwrap();
+        assert!(lp.contains(" obj: + 2 room_1 + 1 x2nd\n"));
+        assert!(lp.contains(" c0: + 1 room_1 + 1 x2nd <= 3\n"));
+        assert!(lp.contains("Binaries\n room_1\nGenerals\n x2nd\nEnd\n"));
+
+        let mut mps = Vec::new();
+        program.write_mps(&mut mps).unwrap();
+        let mps = String::from_utf8(mps).unwrap();
+        assert!(mps.contains("    RHS c0 3\n"));
+        assert!(mps.contains(" BV BND room_1\n LI BND x2nd 0\n"));
+
+        // Cbc writes the column number before the name
+        let solution = "Optimal - objective value 4\n0 room_1 1 2\n1 x2nd 2 1\n";
+        let solved = program.read_solution(solution.as_bytes()).unwrap();
+        assert!((solved.objective - 4.0).abs() < f64::EPSILON);
+        assert!((solved.solution.value(count) - 2.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,69 @@
+//! Project week style allocation where every participant attends exactly one project.
+
+mod allocation;
+mod flow;
+mod model;
+mod problem;
+mod report;
+
+pub use allocation::{ProjectAllocation, ProjectAssignment};
+pub use problem::{Fallback, Project, ProjectParticipant, ProjectPreference, ProjectProblem};
+pub use report::{ProjectAllocationReport, ProjectUtilisation};
+
+use tracing::info;
+
+use crate::objective::optimize;
+use crate::solver::SolverRuns;
+use crate::{OptimizerError, SolverOptions};
+use model::ProjectModel;
+
+/// Allocates every participant of `problem` to exactly one project according to its [`crate::Objective`].
+///
+/// Problems that only restrict the number of participants per project are solved as a min cost flow
+/// within milliseconds, independent of the solver backend.
+///
+/// Participants that aren't eligible for any preferred or fallback project are reported as
+/// [`OptimizerError::UnassignableParticipants`]. Project problems aren't diagnosed, if they are
+/// infeasible otherwise, e.g. because the projects have too few places, the error is
+/// [`OptimizerError::Solver`] with [`good_lp::ResolutionError::Infeasible`] and not
+/// [`OptimizerError::Infeasible`].
+pub fn solve(
+    problem: &ProjectProblem,
+    options: &SolverOptions,
+) -> Result<ProjectAllocation, OptimizerError> {
+    if flow::is_transportation_problem(problem) {
+        info!("solving as a transportation problem");
+        return flow::solve(problem, options);
+    }
+    Ok(ProjectAllocation {
+        lottery: options.lottery,
+        ..optimize::<ProjectModel>(
+            problem,
+            problem.objective,
+            None,
+            None,
+            &SolverRuns::start(options),
+        )?
+        .allocation()
+    })
+}
+
+/// Like [`solve`] but moves as few participants of the `previous` allocation as possible,
+/// e.g. to take late changes of the problem into account after it was published.
+pub fn resolve(
+    problem: &ProjectProblem,
+    previous: &ProjectAllocation,
+    options: &SolverOptions,
+) -> Result<ProjectAllocation, OptimizerError> {
+    Ok(ProjectAllocation {
+        lottery: options.lottery,
+        ..optimize::<ProjectModel>(
+            problem,
+            problem.objective,
+            Some(previous),
+            None,
+            &SolverRuns::start(options),
+        )?
+        .allocation()
+    })
+}
@@ -0,0 +1,59 @@
+use crate::allocation::fingerprint;
+
+/// A participant attending a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProjectAssignment {
+    pub participant: String,
+    pub project: String,
+    /// whether the participant leads the project
+    pub leading: bool,
+    /// whether the participant had no preference and was assigned according to the [`super::Fallback`]
+    pub fallback: bool,
+}
+
+/// The result of a successful project allocation run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProjectAllocation {
+    /// the projects that take place, all others are cancelled
+    pub projects: Vec<String>,
+    pub assignments: Vec<ProjectAssignment>,
+    /// participants without preferences that don't attend any project, see [`super::Fallback`]
+    pub unassigned: Vec<String>,
+    /// the summed rank of all assignments
+    pub objective: f64,
+    /// how far the objective of the last solved model may be from the optimum relative to it,
+    /// zero if it is proven optimal and `None` if the solver doesn't report it.
+    /// `HiGHS` always reports `None` as `good_lp` doesn't return its gap
+    pub gap: Option<f64>,
+    /// the seed of the lottery between equally good allocations, see [`crate::SolverOptions::lottery`]
+    pub lottery: Option<u64>,
+}
+
+impl ProjectAllocation {
+    /// Identifies the held projects and assignments independent of their order,
+    /// see [`crate::Allocation::fingerprint`].
+    #[must_use]
+    pub fn fingerprint(&self) -> String {
+        fingerprint(
+            self.projects
+                .iter()
+                .map(|project| format!("project\t{project}"))
+                .chain(self.assignments.iter().map(|assignment| {
+                    format!(
+                        "assignment\t{}\t{}\t{}\t{}",
+                        assignment.participant,
+                        assignment.project,
+                        assignment.leading,
+                        assignment.fallback
+                    )
+                }))
+                .chain(
+                    self.unassigned
+                        .iter()
+                        .map(|participant| format!("unassigned\t{participant}")),
+                ),
+        )
+    }
+}
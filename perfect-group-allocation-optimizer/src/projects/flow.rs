@@ -0,0 +1,205 @@
+use alloc::collections::BTreeMap;
+
+use good_lp::ResolutionError;
+
+use super::model::{is_eligible, ProjectModel};
+use super::{Fallback, ProjectAllocation, ProjectAssignment, ProjectProblem};
+use crate::model::by_identifier;
+use crate::network::FlowNetwork;
+use crate::objective::split_mix;
+use crate::{Objective, OptimizerError, Rank, SolverOptions};
+
+/// Whether `problem` only restricts the number of participants of the projects, so maximizing
+/// the summed rank is a transportation problem that [`solve`] solves exactly without an ILP solver.
+///
+/// That is the case if no project can be cancelled, there are no groups, quotas or leaders and
+/// participants without preferences stay unassigned.
+pub fn is_transportation_problem(problem: &ProjectProblem) -> bool {
+    problem.objective == Objective::Utilitarian
+        && problem.groups.is_empty()
+        && problem.quotas.is_empty()
+        && problem.fallback == Fallback::Unassigned
+        && problem
+            .projects
+            .iter()
+            .all(|project| project.min_participants == 0 && project.leaders.is_empty())
+}
+
+/// Maximizes the summed rank of a [`is_transportation_problem`] with a min cost flow
+/// from the participants to the projects. Only projects with participants take place.
+pub fn solve(
+    problem: &ProjectProblem,
+    options: &SolverOptions,
+) -> Result<ProjectAllocation, OptimizerError> {
+    // reports the same errors as the model
+    ProjectModel::new(problem)?;
+    let projects = by_identifier(&problem.projects, |project| &project.identifier)?;
+    let participants = by_identifier(&problem.participants, |participant| &participant.identifier)?;
+
+    let mut preferences: BTreeMap<(&str, &str), Rank> = BTreeMap::new();
+    for preference in &problem.preferences {
+        let participant = participants[preference.participant.as_str()];
+        let project = projects[preference.project.as_str()];
+        if is_eligible(participant, project) {
+            let rank = preferences
+                .entry((&participant.identifier, &project.identifier))
+                .or_insert(preference.rank);
+            *rank = (*rank).max(preference.rank);
+        }
+    }
+
+    // the lottery only decides between equally ranked allocations if the weights of all
+    // participants together are less than one rank
+    let lottery_scale = 1_i64 << 24;
+    let rank_scale =
+        lottery_scale.saturating_mul(i64::try_from(participants.len() + 1).unwrap_or(i64::MAX));
+    let mut state = options.lottery.unwrap_or_default();
+
+    let mut network = FlowNetwork::default();
+    let source = network.add_node();
+    let sink = network.add_node();
+    let project_nodes: BTreeMap<&str, usize> = projects
+        .values()
+        .map(|project| {
+            let node = network.add_node();
+            network.add_edge(node, sink, project.max_participants.into(), 0);
+            (project.identifier.as_str(), node)
+        })
+        .collect();
+
+    let mut assigned_participants = 0;
+    let mut unassigned = Vec::new();
+    let mut edges = Vec::new();
+    for participant in participants.values() {
+        let preferred: Vec<(&str, Rank)> = preferences
+            .range((participant.identifier.as_str(), "")..)
+            .take_while(|((preferring, _), _)| *preferring == participant.identifier)
+            .map(|((_, project), rank)| (*project, *rank))
+            .collect();
+        if preferred.is_empty() {
+            unassigned.push(participant.identifier.clone());
+            continue;
+        }
+        let node = network.add_node();
+        network.add_edge(source, node, 1, 0);
+        assigned_participants += 1;
+        for (project, rank) in preferred {
+            let weight = if options.lottery.is_some() {
+                i64::try_from(split_mix(&mut state) >> 40).unwrap_or_default()
+            } else {
+                0
+            };
+            let cost = -(i64::from(rank.0) * rank_scale + weight);
+            let edge = network.add_edge(node, project_nodes[project], 1, cost);
+            edges.push((participant.identifier.as_str(), project, rank, edge));
+        }
+    }
+
+    // every participant with a preference attends exactly one project
+    if network.min_cost_max_flow(source, sink) < assigned_participants {
+        return Err(ResolutionError::Infeasible.into());
+    }
+
+    let mut objective = 0.0;
+    let mut assignments = Vec::new();
+    for (participant, project, rank, edge) in edges {
+        if network.flow(edge) > 0 {
+            objective += f64::from(rank.0);
+            assignments.push(ProjectAssignment {
+                participant: participant.to_owned(),
+                project: project.to_owned(),
+                leading: false,
+                fallback: false,
+            });
+        }
+    }
+    let held: Vec<String> = projects
+        .keys()
+        .filter(|project| {
+            assignments
+                .iter()
+                .any(|assignment| assignment.project == **project)
+        })
+        .map(|project| (*project).to_owned())
+        .collect();
+
+    Ok(ProjectAllocation {
+        projects: held,
+        assignments,
+        unassigned,
+        objective,
+        // the flow is optimal
+        gap: Some(0.0),
+        lottery: options.lottery,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use super::{is_transportation_problem, solve};
+    use crate::objective::optimize;
+    use crate::projects::model::ProjectModel;
+    use crate::projects::{Project, ProjectParticipant, ProjectPreference, ProjectProblem};
+    use crate::solver::SolverRuns;
+    use crate::{Rank, SolverOptions};
+
+    #[test]
+    fn flow_finds_the_optimum_of_the_model() {
+        let project = |identifier: &str, max_participants| Project {
+            identifier: identifier.to_owned(),
+            min_age: 0,
+            max_age: 20,
+            min_participants: 0,
+            max_participants,
+            leaders: vec![],
+            random_assignments: false,
+        };
+        let preferences = [
+            ("p1", "a", 3),
+            ("p1", "b", 1),
+            ("p2", "a", 3),
+            ("p2", "b", 2),
+            ("p3", "b", 3),
+            ("p3", "c", 1),
+            ("p4", "b", 3),
+            ("p4", "c", 2),
+        ];
+        let problem = ProjectProblem {
+            projects: vec![project("a", 1), project("b", 2), project("c", 1)],
+            participants: ["p1", "p2", "p3", "p4"]
+                .map(|identifier| ProjectParticipant {
+                    identifier: identifier.to_owned(),
+                    age: 12,
+                    attributes: BTreeMap::new(),
+                })
+                .into(),
+            preferences: preferences
+                .map(|(participant, project, rank)| ProjectPreference {
+                    participant: participant.to_owned(),
+                    project: project.to_owned(),
+                    rank: Rank(rank),
+                })
+                .into(),
+            ..ProjectProblem::default()
+        };
+        assert!(is_transportation_problem(&problem));
+
+        let options = SolverOptions::default();
+        let flow = solve(&problem, &options).unwrap();
+        let model = optimize::<ProjectModel>(
+            &problem,
+            problem.objective,
+            None,
+            None,
+            &SolverRuns::start(&options),
+        )
+        .unwrap()
+        .allocation();
+
+        assert_eq!(flow.assignments.len(), 4);
+        assert!((flow.objective - 10.0).abs() < f64::EPSILON);
+        assert!((flow.objective - model.objective).abs() < f64::EPSILON);
+    }
+}
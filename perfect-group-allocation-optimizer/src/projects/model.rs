@@ -0,0 +1,605 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use good_lp::{Expression, IntoAffineExpression, Solution, Variable};
+use itertools::Itertools;
+
+use super::{
+    Fallback, Project, ProjectAllocation, ProjectAssignment, ProjectParticipant, ProjectProblem,
+};
+use crate::groups::constrain_groups;
+use crate::model::{by_identifier, Enforcement, HardConstraints};
+use crate::objective::{random_tie_breaker, ParticipantSlots, RankedModel, SolvedModel};
+use crate::program::{eq, geq, leq, Domain, LinearProgram};
+use crate::quotas::{constrain_quotas, quota_terms, QuotaBucket};
+use crate::solver::{maximise, SolverRuns};
+use crate::{OptimizerError, Rank};
+
+pub fn is_eligible(participant: &ProjectParticipant, project: &Project) -> bool {
+    (project.min_age..=project.max_age).contains(&participant.age)
+}
+
+/// Whether the participant attends the project.
+struct AssignmentVariable<'a> {
+    participant: &'a ProjectParticipant,
+    project: &'a Project,
+    leading: bool,
+    fallback: bool,
+    variable: Variable,
+}
+
+/// The ILP model of a [`ProjectProblem`].
+pub struct ProjectModel<'a> {
+    program: LinearProgram,
+    /// whether the project takes place
+    projects: Vec<(&'a Project, Variable)>,
+    assignments: Vec<AssignmentVariable<'a>>,
+    participant_slots: ParticipantSlots,
+    /// the reported objective, without placing participants in fallback projects
+    summed_rank: Expression,
+    unassigned: Vec<&'a ProjectParticipant>,
+    /// participants without preferences that may not attend any project, e.g. if there is no place left
+    optional: Vec<&'a ProjectParticipant>,
+}
+
+/// A [`ProjectModel`] together with the solution found by the solver.
+pub struct SolvedProjectModel<'a> {
+    solution: Box<dyn Solution>,
+    objective: f64,
+    summed_rank: f64,
+    gap: Option<f64>,
+    projects: Vec<(&'a Project, Variable)>,
+    assignments: Vec<AssignmentVariable<'a>>,
+    unassigned: Vec<&'a ProjectParticipant>,
+    optional: Vec<&'a ProjectParticipant>,
+}
+
+impl<'a> ProjectModel<'a> {
+    pub fn new(problem: &'a ProjectProblem) -> Result<Self, OptimizerError> {
+        let projects = by_identifier(&problem.projects, |project| &project.identifier)?;
+        let participants =
+            by_identifier(&problem.participants, |participant| &participant.identifier)?;
+
+        // leaders attend the projects they lead whenever these take place
+        let mut led_projects: BTreeMap<&str, Vec<&Project>> = BTreeMap::new();
+        for project in projects.values() {
+            for leader in &project.leaders {
+                if !participants.contains_key(leader.as_str()) {
+                    return Err(OptimizerError::UnknownParticipant(leader.clone()));
+                }
+                led_projects
+                    .entry(leader.as_str())
+                    .or_default()
+                    .push(project);
+            }
+        }
+
+        let mut preferences_by_participant: BTreeMap<&str, Vec<(&Project, Rank)>> = BTreeMap::new();
+        for preference in &problem.preferences {
+            let participant = participants
+                .get(preference.participant.as_str())
+                .ok_or_else(|| {
+                    OptimizerError::UnknownParticipant(preference.participant.clone())
+                })?;
+            let project = projects
+                .get(preference.project.as_str())
+                .ok_or_else(|| OptimizerError::UnknownProject(preference.project.clone()))?;
+            if is_eligible(participant, project)
+                && !project.leaders.contains(&participant.identifier)
+            {
+                preferences_by_participant
+                    .entry(participant.identifier.as_str())
+                    .or_default()
+                    .push((*project, preference.rank));
+            }
+        }
+
+        // the model must not depend on the order of the input
+        for preferences in preferences_by_participant.values_mut() {
+            preferences.sort_by(|(a, a_rank), (b, b_rank)| {
+                (&a.identifier, a_rank).cmp(&(&b.identifier, b_rank))
+            });
+        }
+
+        // leaders without preferences are allocated according to the fallback
+        // if their projects are cancelled
+        let without_preferences: Vec<&ProjectParticipant> = participants
+            .values()
+            .filter(|participant| {
+                !preferences_by_participant.contains_key(participant.identifier.as_str())
+            })
+            .copied()
+            .collect();
+        let fallback_projects = |participant: &ProjectParticipant| -> Vec<&Project> {
+            if problem.fallback == Fallback::Unassigned {
+                return Vec::new();
+            }
+            projects
+                .values()
+                .filter(|project| {
+                    project.random_assignments
+                        && is_eligible(participant, project)
+                        && !project.leaders.contains(&participant.identifier)
+                })
+                .copied()
+                .collect()
+        };
+        let mut unassigned = Vec::new();
+        let mut unassignable_participants = Vec::new();
+        for participant in &without_preferences {
+            if led_projects.contains_key(participant.identifier.as_str()) {
+                continue;
+            }
+            if problem.fallback == Fallback::Unassigned {
+                unassigned.push(*participant);
+            } else if fallback_projects(participant).is_empty() {
+                unassignable_participants.push(participant.identifier.clone());
+            }
+        }
+        if !unassignable_participants.is_empty() {
+            return Err(OptimizerError::UnassignableParticipants(
+                unassignable_participants,
+            ));
+        }
+
+        let mut program = LinearProgram::default();
+        let mut participants_of_project: BTreeMap<&str, Expression> = BTreeMap::new();
+        let mut assignments = Vec::new();
+        let mut participant_slots = ParticipantSlots::default();
+
+        let held_projects: Vec<(&Project, Variable)> = projects
+            .values()
+            .map(|project| {
+                (
+                    *project,
+                    program.add_variable(project.identifier.clone(), Domain::Binary),
+                )
+            })
+            .collect();
+
+        // how many participants without preferences are placed in a fallback project
+        let mut placed = Expression::default();
+        let mut placeable = 0_u32;
+        // only participants with preferences or leaders take part in the fairness stages
+        let mut fallback_assignments = Vec::new();
+        for participant in participants.values() {
+            if unassigned.contains(participant) {
+                continue;
+            }
+            let preferences = preferences_by_participant
+                .remove(participant.identifier.as_str())
+                .unwrap_or_default();
+            // leading a project counts like getting the best preference
+            let best_rank = preferences
+                .iter()
+                .map(|(_, rank)| *rank)
+                .max()
+                .unwrap_or(Rank(0));
+
+            let mut slot = Vec::new();
+            for (project, rank) in preferences {
+                let assignment = program.add_variable(
+                    project.identifier.clone() + "_" + &participant.identifier,
+                    Domain::Binary,
+                );
+                *participants_of_project
+                    .entry(project.identifier.as_str())
+                    .or_default() += assignment;
+                program.objective += f64::from(rank.0) * assignment;
+                assignments.push(AssignmentVariable {
+                    participant,
+                    project,
+                    leading: false,
+                    fallback: false,
+                    variable: assignment,
+                });
+                slot.push((rank, assignment));
+            }
+            for (project, held) in held_projects
+                .iter()
+                .filter(|(project, _)| project.leaders.contains(&participant.identifier))
+            {
+                let assignment = program.add_variable(
+                    project.identifier.clone() + "_" + &participant.identifier,
+                    Domain::Binary,
+                );
+                program.add_constraint(eq(assignment, held));
+                program.objective += f64::from(best_rank.0) * assignment;
+                assignments.push(AssignmentVariable {
+                    participant,
+                    project,
+                    leading: true,
+                    fallback: false,
+                    variable: assignment,
+                });
+                slot.push((best_rank, assignment));
+            }
+
+            let mut fallbacks = Vec::new();
+            if without_preferences.contains(participant) {
+                for project in fallback_projects(participant) {
+                    let assignment = program.add_variable(
+                        project.identifier.clone() + "_" + &participant.identifier,
+                        Domain::Binary,
+                    );
+                    *participants_of_project
+                        .entry(project.identifier.as_str())
+                        .or_default() += assignment;
+                    assignments.push(AssignmentVariable {
+                        participant,
+                        project,
+                        leading: false,
+                        fallback: true,
+                        variable: assignment,
+                    });
+                    placed += assignment;
+                    fallbacks.push(assignment);
+                }
+                if !fallbacks.is_empty() {
+                    placeable += 1;
+                }
+            }
+
+            // every participant with preferences attends exactly one project, the others at most one
+            let attended: Expression = slot
+                .iter()
+                .map(|(_, assignment)| assignment)
+                .chain(&fallbacks)
+                .sum();
+            if without_preferences.contains(participant) {
+                program.add_constraint(leq(attended, 1));
+            } else {
+                program.add_constraint(eq(attended, 1));
+            }
+            if !slot.is_empty() {
+                participant_slots.push(slot);
+            }
+            fallback_assignments.extend(fallbacks);
+        }
+
+        // how full the fullest project open for fallback assignments is
+        let max_fill = (problem.fallback == Fallback::LeastFull)
+            .then(|| program.add_variable("max_fill", Domain::NonNegative));
+        match problem.fallback {
+            Fallback::Unassigned => {}
+            Fallback::Random { seed } => {
+                program.tie_breaker += random_tie_breaker(seed, &fallback_assignments);
+            }
+            Fallback::LeastFull => {
+                if let Some(max_fill) = max_fill {
+                    program.tie_breaker -= 0.5 * max_fill;
+                }
+            }
+        }
+
+        for (project, held) in &held_projects {
+            // leaders don't count as participants
+            let participants_of_project = participants_of_project
+                .remove(project.identifier.as_str())
+                .unwrap_or_default();
+            // a cancelled project has no participants
+            program.add_constraint(leq(
+                participants_of_project.clone(),
+                f64::from(project.max_participants) * *held,
+            ));
+            if let Some(max_fill) = max_fill.filter(|_| project.random_assignments) {
+                program.add_constraint(leq(
+                    participants_of_project.clone(),
+                    f64::from(project.max_participants) * max_fill,
+                ));
+            }
+            program.add_constraint(geq(
+                participants_of_project,
+                f64::from(project.min_participants) * *held,
+            ));
+        }
+
+        let buckets: Vec<BTreeMap<&str, Variable>> = held_projects
+            .iter()
+            .map(|(project, _)| {
+                assignments
+                    .iter()
+                    .filter(|assignment| assignment.project == *project)
+                    .map(|assignment| {
+                        (
+                            assignment.participant.identifier.as_str(),
+                            assignment.variable,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        // the project model isn't diagnosed, its hard constraints are always enforced
+        let mut hard_constraints = HardConstraints::new(&Enforcement::All);
+        constrain_groups(
+            &problem.groups,
+            |participant| participants.contains_key(participant),
+            &buckets,
+            &mut program,
+            &mut hard_constraints,
+        )?;
+
+        let attributes = participants
+            .iter()
+            .map(|(identifier, participant)| (*identifier, &participant.attributes))
+            .collect();
+        let quota_buckets: Vec<QuotaBucket<Expression>> = held_projects
+            .iter()
+            .map(|(project, held)| QuotaBucket {
+                target: project.identifier.as_str(),
+                timeslot: None,
+                held: (*held).into(),
+                participants: assignments
+                    .iter()
+                    .filter(|assignment| assignment.project == *project && !assignment.leading)
+                    .map(|assignment| {
+                        (
+                            assignment.participant.identifier.as_str(),
+                            assignment.variable.into(),
+                        )
+                    })
+                    .collect(),
+            })
+            .collect();
+        let terms = quota_terms(
+            &problem.quotas,
+            &attributes,
+            &quota_buckets,
+            |project| projects.contains_key(project),
+            OptimizerError::UnknownProject,
+        )?;
+        constrain_quotas(terms, &mut program, &mut hard_constraints);
+
+        // scaling the summed rank above the number of placeable participants lets placing them
+        // only decide between allocations with the same summed rank, never displacing a preference
+        let summed_rank = program.objective.clone();
+        if placeable > 0 {
+            program.objective = f64::from(placeable + 1) * summed_rank.clone() + placed;
+        }
+
+        Ok(Self {
+            program,
+            summed_rank,
+            projects: held_projects,
+            assignments,
+            participant_slots,
+            optional: without_preferences
+                .into_iter()
+                .filter(|participant| !unassigned.contains(participant))
+                .collect(),
+            unassigned,
+        })
+    }
+
+    pub fn solve(self, runs: &SolverRuns) -> Result<SolvedProjectModel<'a>, OptimizerError> {
+        let solved = maximise(self.program, runs)?;
+        let summed_rank = self.summed_rank.constant()
+            + self
+                .summed_rank
+                .linear_coefficients()
+                .map(|(variable, factor)| factor * solved.solution.value(variable))
+                .sum::<f64>();
+
+        Ok(SolvedProjectModel {
+            solution: solved.solution,
+            objective: solved.objective,
+            summed_rank,
+            gap: solved.gap,
+            projects: self.projects,
+            assignments: self.assignments,
+            unassigned: self.unassigned,
+            optional: self.optional,
+        })
+    }
+}
+
+impl SolvedProjectModel<'_> {
+    pub fn allocation(&self) -> ProjectAllocation {
+        ProjectAllocation {
+            projects: self
+                .projects
+                .iter()
+                .filter(|(_, held)| self.solution.value(*held) > 0.5)
+                .map(|(project, _)| project.identifier.clone())
+                .collect(),
+            assignments: self
+                .assignments
+                .iter()
+                .filter(|assignment| self.solution.value(assignment.variable) > 0.5)
+                .map(|assignment| ProjectAssignment {
+                    participant: assignment.participant.identifier.clone(),
+                    project: assignment.project.identifier.clone(),
+                    leading: assignment.leading,
+                    fallback: assignment.fallback,
+                })
+                .collect(),
+            unassigned: self
+                .unassigned
+                .iter()
+                .chain(self.optional.iter().filter(|participant| {
+                    !self.assignments.iter().any(|assignment| {
+                        assignment.participant.identifier == participant.identifier
+                            && self.solution.value(assignment.variable) > 0.5
+                    })
+                }))
+                .map(|participant| participant.identifier.clone())
+                .sorted()
+                .collect(),
+            objective: self.summed_rank,
+            gap: self.gap,
+            lottery: None,
+        }
+    }
+}
+
+impl<'a> RankedModel<'a> for ProjectModel<'a> {
+    type Problem = ProjectProblem;
+    type Solved = SolvedProjectModel<'a>;
+    type Allocation = ProjectAllocation;
+
+    fn build(problem: &'a ProjectProblem) -> Result<Self, OptimizerError> {
+        Self::new(problem)
+    }
+
+    fn participant_slots(&self) -> &ParticipantSlots {
+        &self.participant_slots
+    }
+
+    fn program(&mut self) -> &mut LinearProgram {
+        &mut self.program
+    }
+
+    fn kept_assignments(&self, previous: &ProjectAllocation) -> Expression {
+        let previous: BTreeSet<(&str, &str)> = previous
+            .assignments
+            .iter()
+            .map(|assignment| (assignment.participant.as_str(), assignment.project.as_str()))
+            .collect();
+        self.assignments
+            .iter()
+            .filter(|assignment| {
+                previous.contains(&(
+                    assignment.participant.identifier.as_str(),
+                    assignment.project.identifier.as_str(),
+                ))
+            })
+            .map(|assignment| assignment.variable)
+            .sum()
+    }
+
+    /// Every participant attends one project, so only the best ranked projects are top preferences.
+    fn top_preferences(&self) -> Expression {
+        self.participant_slots.best_ranked()
+    }
+
+    fn solve(self, runs: &SolverRuns) -> Result<SolvedProjectModel<'a>, OptimizerError> {
+        self.solve(runs)
+    }
+}
+
+impl SolvedModel for SolvedProjectModel<'_> {
+    fn objective(&self) -> f64 {
+        self.objective
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use good_lp::ResolutionError;
+
+    use crate::projects::{
+        solve, Fallback, Project, ProjectParticipant, ProjectPreference, ProjectProblem,
+    };
+    use crate::{OptimizerError, Rank, SolverOptions};
+
+    fn project(identifier: &str, min: u8, max: u8, leaders: &[&str], random: bool) -> Project {
+        Project {
+            identifier: identifier.to_owned(),
+            min_age: 0,
+            max_age: 20,
+            min_participants: min,
+            max_participants: max,
+            leaders: leaders.iter().map(|leader| (*leader).to_owned()).collect(),
+            random_assignments: random,
+        }
+    }
+
+    /// `ben` prefers `art`, everyone else has no preferences
+    fn problem(
+        projects: Vec<Project>,
+        participants: &[&str],
+        fallback: Fallback,
+    ) -> ProjectProblem {
+        ProjectProblem {
+            projects,
+            participants: participants
+                .iter()
+                .map(|identifier| ProjectParticipant {
+                    identifier: (*identifier).to_owned(),
+                    age: 12,
+                    attributes: BTreeMap::new(),
+                })
+                .collect(),
+            preferences: vec![ProjectPreference {
+                participant: "ben".to_owned(),
+                project: "art".to_owned(),
+                rank: Rank(1),
+            }],
+            fallback,
+            ..ProjectProblem::default()
+        }
+    }
+
+    #[test]
+    fn leader_without_preferences_falls_back_if_cancelled() {
+        let problem = problem(
+            vec![
+                project("art", 0, 10, &[], true),
+                project("chess", 3, 10, &["lea"], false),
+            ],
+            &["ben", "lea"],
+            Fallback::Random { seed: 1 },
+        );
+        let allocation = solve(&problem, &SolverOptions::default()).unwrap();
+
+        assert_eq!(allocation.projects, vec!["art".to_owned()]);
+        assert!(allocation.unassigned.is_empty());
+        assert!(allocation.assignments.iter().any(|assignment| {
+            assignment.participant == "lea" && assignment.project == "art" && assignment.fallback
+        }));
+    }
+
+    #[test]
+    fn unassigns_fallback_participants_without_place() {
+        let problem = problem(
+            vec![project("art", 0, 2, &[], true)],
+            &["ben", "tom", "uwe"],
+            Fallback::LeastFull,
+        );
+        let allocation = solve(&problem, &SolverOptions::default()).unwrap();
+
+        assert_eq!(allocation.assignments.len(), 2);
+        assert_eq!(allocation.unassigned.len(), 1);
+    }
+
+    #[test]
+    fn fallback_participants_do_not_displace_preferences() {
+        let mut problem = problem(
+            vec![
+                project("art", 0, 2, &[], true),
+                project("chess", 0, 2, &[], false),
+            ],
+            &["ben", "tom", "uwe"],
+            Fallback::LeastFull,
+        );
+        problem.preferences[0].rank = Rank(5);
+        problem.preferences.push(ProjectPreference {
+            participant: "ben".to_owned(),
+            project: "chess".to_owned(),
+            rank: Rank(1),
+        });
+        let allocation = solve(&problem, &SolverOptions::default()).unwrap();
+
+        assert!(allocation
+            .assignments
+            .iter()
+            .any(|assignment| { assignment.participant == "ben" && assignment.project == "art" }));
+        assert_eq!(allocation.assignments.len(), 2);
+        assert_eq!(allocation.unassigned.len(), 1);
+        assert!((allocation.objective - 5.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn reports_infeasible_problem_as_solver_error() {
+        let problem = problem(
+            vec![project("art", 2, 10, &[], true)],
+            &["ben"],
+            Fallback::LeastFull,
+        );
+        assert!(matches!(
+            solve(&problem, &SolverOptions::default()),
+            Err(OptimizerError::Solver(ResolutionError::Infeasible))
+        ));
+    }
+}
@@ -0,0 +1,78 @@
+use alloc::collections::BTreeMap;
+
+use crate::{Objective, ParticipantGroup, Quota, Rank};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Project {
+    pub identifier: String,
+    /// only participants with an age in `min_age..=max_age` may attend
+    pub min_age: u8,
+    pub max_age: u8,
+    /// the project is cancelled if it doesn't get this many participants
+    pub min_participants: u8,
+    pub max_participants: u8,
+    /// identifiers of the [`ProjectParticipant`]s leading this project. They attend it whenever it
+    /// takes place and are allocated like everyone else otherwise. Leaders don't count as participants.
+    pub leaders: Vec<String>,
+    /// whether participants without preferences may be assigned to this project, see [`Fallback`]
+    pub random_assignments: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProjectParticipant {
+    pub identifier: String,
+    /// may also be the grade, as long as the projects use the same unit
+    pub age: u8,
+    /// e.g. the class or gender, see [`Quota`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributes: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProjectPreference {
+    /// identifier of the [`ProjectParticipant`]
+    pub participant: String,
+    /// identifier of the [`Project`]
+    pub project: String,
+    pub rank: Rank,
+}
+
+/// What happens to participants without a preference for a project they are eligible for,
+/// including leaders whose projects are cancelled.
+///
+/// They only fill places that aren't needed to give the participants with preferences
+/// the best summed rank, which is the reported objective.
+///
+/// Only project problems have a fallback, participants of a [`crate::AllocationProblem`]
+/// without preferences don't attend any workshop, see [`crate::AllocationReport::unassigned`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fallback {
+    /// they don't attend any project and are listed in [`super::ProjectAllocation::unassigned`]
+    #[default]
+    Unassigned,
+    /// they are randomly distributed over the places left in the projects with `random_assignments`,
+    /// the ones that don't fit are unassigned
+    Random { seed: u64 },
+    /// they fill up the projects with `random_assignments` such that the fullest of them,
+    /// relative to its `max_participants`, is as empty as possible, the ones that don't fit are unassigned
+    LeastFull,
+}
+
+/// All input data of a single project allocation run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ProjectProblem {
+    pub projects: Vec<Project>,
+    pub participants: Vec<ProjectParticipant>,
+    pub preferences: Vec<ProjectPreference>,
+    pub groups: Vec<ParticipantGroup>,
+    /// leaders don't count towards the quotas
+    pub quotas: Vec<Quota>,
+    pub objective: Objective,
+    pub fallback: Fallback,
+}
@@ -0,0 +1,97 @@
+use alloc::collections::BTreeMap;
+
+use super::{ProjectAllocation, ProjectProblem};
+use crate::Rank;
+
+/// How many participants attend a project compared to its limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProjectUtilisation {
+    pub project: String,
+    /// whether the project takes place
+    pub held: bool,
+    /// leaders don't count as participants
+    pub participants: usize,
+    pub leaders: usize,
+    pub min_participants: u8,
+    pub max_participants: u8,
+}
+
+/// Statistics about the quality of a [`ProjectAllocation`], see [`crate::AllocationReport`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProjectAllocationReport {
+    /// how many participants got each rank, without leaders and fallback assignments
+    pub ranks: BTreeMap<Rank, usize>,
+    /// how many participants were assigned according to the [`super::Fallback`]
+    pub fallback: usize,
+    pub projects: Vec<ProjectUtilisation>,
+    /// see [`ProjectAllocation::unassigned`]
+    pub unassigned: Vec<String>,
+    /// see [`ProjectAllocation::objective`]
+    pub objective: f64,
+    /// see [`ProjectAllocation::gap`]
+    pub gap: Option<f64>,
+}
+
+impl ProjectAllocationReport {
+    /// Computes the statistics of `allocation`, which has to be an allocation of `problem`.
+    #[must_use]
+    pub fn new(problem: &ProjectProblem, allocation: &ProjectAllocation) -> Self {
+        let mut preferences: BTreeMap<(&str, &str), Rank> = BTreeMap::new();
+        for preference in &problem.preferences {
+            let rank = preferences
+                .entry((&preference.participant, &preference.project))
+                .or_insert(preference.rank);
+            *rank = (*rank).max(preference.rank);
+        }
+
+        let mut ranks = BTreeMap::new();
+        let mut fallback = 0;
+        // the number of participants and leaders by project
+        let mut attendance: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        for assignment in &allocation.assignments {
+            let (participants, leaders) = attendance.entry(&assignment.project).or_default();
+            if assignment.leading {
+                *leaders += 1;
+                continue;
+            }
+            *participants += 1;
+            if assignment.fallback {
+                fallback += 1;
+            } else if let Some(rank) =
+                preferences.get(&(assignment.participant.as_str(), assignment.project.as_str()))
+            {
+                *ranks.entry(*rank).or_default() += 1;
+            }
+        }
+
+        let projects = problem
+            .projects
+            .iter()
+            .map(|project| {
+                let (participants, leaders) = attendance
+                    .get(project.identifier.as_str())
+                    .copied()
+                    .unwrap_or_default();
+                ProjectUtilisation {
+                    project: project.identifier.clone(),
+                    held: allocation.projects.contains(&project.identifier),
+                    participants,
+                    leaders,
+                    min_participants: project.min_participants,
+                    max_participants: project.max_participants,
+                }
+            })
+            .collect();
+
+        Self {
+            ranks,
+            fallback,
+            projects,
+            unassigned: allocation.unassigned.clone(),
+            objective: allocation.objective,
+            gap: allocation.gap,
+        }
+    }
+}
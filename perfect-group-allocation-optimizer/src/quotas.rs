@@ -0,0 +1,441 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::iter::Sum;
+
+use good_lp::Expression;
+use itertools::Itertools;
+
+use crate::diagnosis::HardConstraint;
+use crate::model::HardConstraints;
+use crate::program::{geq, leq, Domain, LinearProgram, Sense};
+use crate::OptimizerError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QuotaLimit {
+    /// a number of participants per project or workshop
+    Participants(u8),
+    /// a percentage of all participants of the project or workshop, rounded up for a minimum
+    /// and down for a maximum as only whole participants can attend
+    Percent(u8),
+}
+
+impl QuotaLimit {
+    /// the limit for a bucket of `held` projects or workshops with `total` participants,
+    /// percentages are rounded with `round`
+    fn bound(self, held: f64, total: f64, round: fn(f64) -> f64) -> f64 {
+        match self {
+            Self::Participants(participants) => f64::from(participants) * held,
+            // the product is integral, so the division is the closest float to the exact limit
+            Self::Percent(percent) => round(f64::from(percent) * total / 100.0),
+        }
+    }
+}
+
+/// Restricts how many participants with an attribute attend a project or workshop,
+/// e.g. "no more than 40% from one class" or "at least 2 from the lower grades".
+///
+/// For workshops a quota applies to every topic and timeslot its workshops start in separately.
+/// The workshops of a topic held in several rooms at once count together and their percentages
+/// are rounded once for all of them, the participants are only distributed over the rooms
+/// after the optimization.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quota {
+    /// identifiers of the projects or workshop topics this quota applies to, all of them if empty
+    pub targets: Vec<String>,
+    /// the key of the participant attribute, e.g. `class`
+    pub attribute: String,
+    /// only participants with this value count, e.g. `lower`. If `None` the quota applies
+    /// to every value of the attribute separately, e.g. to every class
+    pub value: Option<String>,
+    pub min: Option<QuotaLimit>,
+    pub max: Option<QuotaLimit>,
+    /// `None` makes this a hard constraint, otherwise every participant more or less than allowed
+    /// reduces the summed rank by this amount
+    pub penalty: Option<u16>,
+}
+
+/// The participants allocated to the projects or workshops of a target together.
+///
+/// For workshops these are all workshops of a topic starting in a timeslot,
+/// so the quotas apply to them together and not to every room on its own.
+pub struct QuotaBucket<'a, T> {
+    /// identifier of the project or workshop topic
+    pub target: &'a str,
+    /// the timeslot the workshops start in, `None` for projects
+    pub timeslot: Option<&'a str>,
+    /// how many projects or workshops take place
+    pub held: T,
+    /// the participants together with whether they attend, e.g. their assignment variables
+    pub participants: Vec<(&'a str, T)>,
+}
+
+/// A [`Quota`] applied to one value of its attribute in one [`QuotaBucket`].
+pub struct QuotaTerm<'q, T> {
+    pub quota: &'q Quota,
+    pub value: &'q str,
+    pub target: String,
+    pub timeslot: Option<String>,
+    /// how many participants with the value attend
+    pub count: T,
+    /// see [`QuotaBucket::held`]
+    pub held: T,
+    /// how many participants attend in total
+    pub total: T,
+}
+
+impl<T> QuotaTerm<'_, T> {
+    fn hard_constraint(&self) -> HardConstraint {
+        HardConstraint::Quota {
+            target: self.target.clone(),
+            timeslot: self.timeslot.clone(),
+            attribute: self.quota.attribute.clone(),
+            value: self.value.to_owned(),
+        }
+    }
+}
+
+impl QuotaTerm<'_, f64> {
+    /// how many participants are more or less than allowed
+    pub fn violation(&self) -> f64 {
+        self.quota.max.map_or(0.0, |max| {
+            (self.count - max.bound(self.held, self.total, f64::floor)).max(0.0)
+        }) + self.quota.min.map_or(0.0, |min| {
+            (min.bound(self.held, self.total, f64::ceil) - self.count).max(0.0)
+        })
+    }
+}
+
+/// Applies `quotas` to all `buckets` of their targets, `attributes` contains the attributes
+/// of every participant. Unknown targets are reported using `unknown_target`.
+pub fn quota_terms<'q, T>(
+    quotas: &'q [Quota],
+    attributes: &BTreeMap<&str, &'q BTreeMap<String, String>>,
+    buckets: &[QuotaBucket<'_, T>],
+    is_target: impl Fn(&str) -> bool,
+    unknown_target: fn(String) -> OptimizerError,
+) -> Result<Vec<QuotaTerm<'q, T>>, OptimizerError>
+where
+    T: Clone + Sum<T>,
+{
+    let mut terms = Vec::new();
+    // the model must not depend on the order of the input
+    for quota in quotas.iter().sorted() {
+        if let Some(target) = quota.targets.iter().find(|target| !is_target(target)) {
+            return Err(unknown_target(target.clone()));
+        }
+        let values: BTreeSet<&str> = quota.value.as_deref().map_or_else(
+            || {
+                attributes
+                    .values()
+                    .filter_map(|attributes| attributes.get(&quota.attribute))
+                    .map(String::as_str)
+                    .collect()
+            },
+            |value| BTreeSet::from([value]),
+        );
+
+        for bucket in buckets.iter().filter(|bucket| {
+            quota.targets.is_empty() || quota.targets.iter().any(|target| target == bucket.target)
+        }) {
+            let total: T = bucket
+                .participants
+                .iter()
+                .map(|(_, attends)| attends.clone())
+                .sum();
+            for value in &values {
+                let count = bucket
+                    .participants
+                    .iter()
+                    .filter(|(participant, _)| {
+                        attributes
+                            .get(participant)
+                            .and_then(|attributes| attributes.get(&quota.attribute))
+                            .is_some_and(|attribute| attribute == value)
+                    })
+                    .map(|(_, attends)| attends.clone())
+                    .sum();
+                terms.push(QuotaTerm {
+                    quota,
+                    value,
+                    target: bucket.target.to_owned(),
+                    timeslot: bucket.timeslot.map(ToOwned::to_owned),
+                    count,
+                    held: bucket.held.clone(),
+                    total: total.clone(),
+                });
+            }
+        }
+    }
+    Ok(terms)
+}
+
+/// The limit of `term` as an expression, a percentage is rounded by an integer variable
+/// that is at most (`Sense::LessOrEqual`) or at least the exact limit.
+fn limit_expression(
+    program: &mut LinearProgram,
+    term: &QuotaTerm<'_, Expression>,
+    limit: QuotaLimit,
+    name: String,
+    rounding: Sense,
+) -> Expression {
+    match limit {
+        QuotaLimit::Participants(participants) => f64::from(participants) * term.held.clone(),
+        QuotaLimit::Percent(percent) => {
+            let rounded = program.add_variable(name, Domain::Integer);
+            let exact = f64::from(percent) * term.total.clone();
+            program.add_constraint(match rounding {
+                Sense::LessOrEqual => leq(100.0 * rounded, exact),
+                Sense::Equal | Sense::GreaterOrEqual => geq(100.0 * rounded, exact),
+            });
+            rounded.into()
+        }
+    }
+}
+
+/// Adds the constraints of the quota `terms` to a model, the hard ones as [`HardConstraint::Quota`].
+///
+/// As percentages are rounded to whole participants the objective stays integral.
+pub fn constrain_quotas(
+    terms: Vec<QuotaTerm<'_, Expression>>,
+    program: &mut LinearProgram,
+    hard_constraints: &mut HardConstraints,
+) {
+    for (index, term) in terms.into_iter().enumerate() {
+        let max = term.quota.max.map(|max| {
+            limit_expression(
+                program,
+                &term,
+                max,
+                format!("quota_{index}_max"),
+                Sense::LessOrEqual,
+            )
+        });
+        let min = term.quota.min.map(|min| {
+            limit_expression(
+                program,
+                &term,
+                min,
+                format!("quota_{index}_min"),
+                Sense::GreaterOrEqual,
+            )
+        });
+        if let Some(penalty) = term.quota.penalty {
+            // how many participants are more or less than allowed
+            let violation = program.add_variable(format!("quota_{index}"), Domain::NonNegative);
+            program.objective -= f64::from(penalty) * violation;
+            if let Some(max) = max {
+                program.add_constraint(leq(term.count.clone(), max + violation));
+            }
+            if let Some(min) = min {
+                program.add_constraint(geq(term.count.clone() + violation, min));
+            }
+        } else {
+            if let Some(max) = max {
+                hard_constraints.add(
+                    program,
+                    term.hard_constraint(),
+                    term.count.clone() - max,
+                    Sense::LessOrEqual,
+                    0.0,
+                );
+            }
+            if let Some(min) = min {
+                hard_constraints.add(
+                    program,
+                    term.hard_constraint(),
+                    term.count.clone() - min,
+                    Sense::GreaterOrEqual,
+                    0.0,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use super::{quota_terms, QuotaBucket};
+    use crate::{
+        solve, AllocationProblem, FreeSlots, HardConstraint, OptimizerError, Participant,
+        Preference, Quota, QuotaLimit, Rank, Room, RoomSize, SolverOptions, Timeslot, Workshop,
+        WorkshopDuration, WorkshopTopic, WorkshopTopicSize,
+    };
+
+    fn class_attributes(classes: &[(&str, &str)]) -> Vec<(String, BTreeMap<String, String>)> {
+        classes
+            .iter()
+            .map(|(participant, class)| {
+                (
+                    (*participant).to_owned(),
+                    BTreeMap::from([("class".to_owned(), (*class).to_owned())]),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rounds_percentages_to_whole_participants() {
+        let classes = class_attributes(&[("anna", "a"), ("ben", "a"), ("carl", "b")]);
+        let attributes = classes
+            .iter()
+            .map(|(participant, attributes)| (participant.as_str(), attributes))
+            .collect();
+        let quotas = [Quota {
+            targets: vec![],
+            attribute: "class".to_owned(),
+            value: Some("a".to_owned()),
+            min: Some(QuotaLimit::Percent(50)),
+            max: Some(QuotaLimit::Percent(50)),
+            penalty: Some(1),
+        }];
+        let bucket = |participants: &[&'static str]| QuotaBucket {
+            target: "topic",
+            timeslot: None,
+            held: 1.0,
+            participants: participants
+                .iter()
+                .map(|participant| (*participant, 1.0))
+                .collect(),
+        };
+        let terms = quota_terms(
+            &quotas,
+            &attributes,
+            &[bucket(&["anna", "ben", "carl"]), bucket(&["anna", "carl"])],
+            |_| true,
+            OptimizerError::UnknownTopic,
+        )
+        .unwrap();
+        // at most one and at least two of three participants
+        assert!((terms[0].violation() - 1.0).abs() < f64::EPSILON);
+        // exactly one of two participants
+        assert!(terms[1].violation().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn diagnoses_hard_quota() {
+        let participants = class_attributes(&[("anna", "a"), ("ben", "b")]);
+        let problem = AllocationProblem {
+            timeslots: vec![Timeslot {
+                identifier: "morning".to_owned(),
+            }],
+            rooms: vec![Room {
+                identifier: "room".to_owned(),
+                requirements: vec![],
+                max_size: RoomSize(10),
+            }],
+            topics: vec![WorkshopTopic {
+                identifier: "topic".to_owned(),
+                requirements: vec![],
+                max_size: WorkshopTopicSize(10),
+                repetitions: None,
+                duration: WorkshopDuration(1),
+            }],
+            workshops: vec![Workshop {
+                topic: "topic".to_owned(),
+                timeslot: "morning".to_owned(),
+            }],
+            preferences: participants
+                .iter()
+                .map(|(participant, _)| Preference {
+                    participant: participant.clone(),
+                    topic: "topic".to_owned(),
+                    rank: Rank(1),
+                })
+                .collect(),
+            participants: participants
+                .into_iter()
+                .map(|(identifier, attributes)| Participant {
+                    identifier,
+                    timeslots: None,
+                    attributes,
+                })
+                .collect(),
+            // 40% of two participants is rounded down to nobody
+            quotas: vec![Quota {
+                targets: vec![],
+                attribute: "class".to_owned(),
+                value: None,
+                min: None,
+                max: Some(QuotaLimit::Percent(40)),
+                penalty: None,
+            }],
+            free_slots: FreeSlots::Forbidden,
+            ..AllocationProblem::default()
+        };
+        let Err(OptimizerError::Infeasible(report)) = solve(&problem, &SolverOptions::default())
+        else {
+            panic!("problem should be infeasible");
+        };
+        assert!(report.timeslots_short_of_rooms.is_empty());
+        assert!(report.conflicting.iter().any(|constraint| matches!(
+            constraint,
+            HardConstraint::Quota { timeslot: Some(timeslot), .. } if timeslot == "morning"
+        )));
+    }
+
+    #[test]
+    fn applies_percentages_to_all_rooms_of_a_workshop_start() {
+        let participants =
+            class_attributes(&[("anna", "a"), ("ben", "b"), ("carl", "b"), ("dora", "b")]);
+        let problem = AllocationProblem {
+            timeslots: vec![Timeslot {
+                identifier: "morning".to_owned(),
+            }],
+            rooms: ["left", "right"]
+                .map(|identifier| Room {
+                    identifier: identifier.to_owned(),
+                    requirements: vec![],
+                    max_size: RoomSize(2),
+                })
+                .into(),
+            topics: vec![WorkshopTopic {
+                identifier: "topic".to_owned(),
+                requirements: vec![],
+                max_size: WorkshopTopicSize(2),
+                repetitions: None,
+                duration: WorkshopDuration(1),
+            }],
+            workshops: vec![
+                Workshop {
+                    topic: "topic".to_owned(),
+                    timeslot: "morning".to_owned(),
+                };
+                2
+            ],
+            preferences: participants
+                .iter()
+                .map(|(participant, _)| Preference {
+                    participant: participant.clone(),
+                    topic: "topic".to_owned(),
+                    rank: Rank(1),
+                })
+                .collect(),
+            participants: participants
+                .into_iter()
+                .map(|(identifier, attributes)| Participant {
+                    identifier,
+                    timeslots: None,
+                    attributes,
+                })
+                .collect(),
+            // 33% of the four participants in both rooms allow one, 33% of two in a room wouldn't
+            quotas: vec![Quota {
+                targets: vec![],
+                attribute: "class".to_owned(),
+                value: Some("a".to_owned()),
+                min: None,
+                max: Some(QuotaLimit::Percent(33)),
+                penalty: None,
+            }],
+            free_slots: FreeSlots::Forbidden,
+            ..AllocationProblem::default()
+        };
+        let allocation = solve(&problem, &SolverOptions::default()).unwrap();
+
+        assert_eq!(allocation.workshops.len(), 2);
+        assert_eq!(allocation.assignments.len(), 4);
+    }
+}
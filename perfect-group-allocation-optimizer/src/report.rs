@@ -0,0 +1,357 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use crate::model::{
+    fulfills_requirements, rooms_in_timeslots, rooms_spanning, spanned_timeslots, workshop_starts,
+};
+use crate::{Allocation, AllocationProblem, Rank, WorkshopTopic};
+
+/// How many participants attend a workshop compared to the seats it has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorkshopUtilisation {
+    pub topic: String,
+    /// the timeslot the workshop starts in
+    pub timeslot: String,
+    pub room: String,
+    pub participants: usize,
+    /// the smaller of the sizes of the room and the topic, the smallest size the room has
+    /// in the timeslots the workshop spans
+    pub capacity: usize,
+}
+
+/// How many workshops of a topic are held, including optional topics that aren't held at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopicUtilisation {
+    pub topic: String,
+    /// workshops spanning multiple timeslots count once
+    pub workshops: usize,
+    pub participants: usize,
+    /// whether any room fulfills the requirements of the topic, otherwise it can't be held
+    pub has_matching_room: bool,
+}
+
+/// How the rooms are used in a timeslot, workshops spanning multiple timeslots count in each of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeslotUtilisation {
+    pub timeslot: String,
+    /// rooms with a workshop
+    pub used_rooms: usize,
+    /// rooms available in this timeslot
+    pub rooms: usize,
+    pub participants: usize,
+    /// the summed size of all available rooms
+    pub seats: usize,
+}
+
+/// A participant that doesn't attend any workshop in a timeslot they are present in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnassignedParticipant {
+    pub participant: String,
+    pub timeslot: String,
+}
+
+/// Statistics about the quality of an [`Allocation`], e.g. to display them next to it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AllocationReport {
+    /// how many assignments got each rank, an assignment to a workshop spanning multiple timeslots
+    /// counts once
+    pub ranks: BTreeMap<Rank, usize>,
+    pub workshops: Vec<WorkshopUtilisation>,
+    /// every topic of the problem in its order
+    pub topics: Vec<TopicUtilisation>,
+    pub timeslots: Vec<TimeslotUtilisation>,
+    pub unassigned: Vec<UnassignedParticipant>,
+    /// see [`Allocation::objective`]
+    pub objective: f64,
+    /// see [`Allocation::gap`]
+    pub gap: Option<f64>,
+}
+
+impl AllocationReport {
+    /// Computes the statistics of `allocation`, which has to be an allocation of `problem`.
+    #[must_use]
+    pub fn new(problem: &AllocationProblem, allocation: &Allocation) -> Self {
+        let mut preferences: BTreeMap<(&str, &str), Rank> = BTreeMap::new();
+        for preference in &problem.preferences {
+            let rank = preferences
+                .entry((&preference.participant, &preference.topic))
+                .or_insert(preference.rank);
+            *rank = (*rank).max(preference.rank);
+        }
+
+        let topics: BTreeMap<&str, &WorkshopTopic> = problem
+            .topics
+            .iter()
+            .map(|topic| (topic.identifier.as_str(), topic))
+            .collect();
+
+        // the timeslot and room of every workshop in the timeslot it starts in
+        let starts: BTreeSet<(&str, &str)> = workshop_starts(problem, allocation)
+            .into_iter()
+            .map(|(_, timeslot, room)| (timeslot, room))
+            .collect();
+
+        let mut ranks = BTreeMap::new();
+        // the number of participants by timeslot and room
+        let mut attendance: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+        for assignment in &allocation.assignments {
+            let workshop = (assignment.timeslot.as_str(), assignment.room.as_str());
+            if let Some(rank) = preferences
+                .get(&(assignment.participant.as_str(), assignment.topic.as_str()))
+                .filter(|_| starts.contains(&workshop))
+            {
+                *ranks.entry(*rank).or_default() += 1;
+            }
+            *attendance.entry(workshop).or_default() += 1;
+        }
+
+        // an invalid problem has no allocation to report on
+        let rooms_in_timeslots = rooms_in_timeslots(problem).unwrap_or_default();
+        let room_sizes: BTreeMap<(&str, &str), usize> = rooms_in_timeslots
+            .iter()
+            .flat_map(|(timeslot, rooms)| {
+                rooms.iter().map(|room_in_timeslot| {
+                    (
+                        (*timeslot, room_in_timeslot.room.identifier.as_str()),
+                        room_in_timeslot.max_size.0.into(),
+                    )
+                })
+            })
+            .collect();
+        let workshops: Vec<WorkshopUtilisation> = allocation
+            .workshops
+            .iter()
+            .filter(|workshop| {
+                starts.contains(&(workshop.timeslot.as_str(), workshop.room.as_str()))
+            })
+            .map(|workshop| WorkshopUtilisation {
+                topic: workshop.topic.clone(),
+                timeslot: workshop.timeslot.clone(),
+                room: workshop.room.clone(),
+                participants: attendance
+                    .get(&(workshop.timeslot.as_str(), workshop.room.as_str()))
+                    .copied()
+                    .unwrap_or_default(),
+                // like the model
+                capacity: topics
+                    .get(workshop.topic.as_str())
+                    .and_then(|topic| {
+                        let spanned =
+                            spanned_timeslots(&problem.timeslots, topic, &workshop.timeslot)?;
+                        rooms_spanning(&rooms_in_timeslots, spanned)
+                            .into_iter()
+                            .find(|room_in_timeslot| {
+                                room_in_timeslot.room.identifier == workshop.room
+                            })
+                            .map(|room_in_timeslot| room_in_timeslot.capacity(topic).into())
+                    })
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        let topics = problem
+            .topics
+            .iter()
+            .map(|topic| {
+                let held: Vec<&WorkshopUtilisation> = workshops
+                    .iter()
+                    .filter(|workshop| workshop.topic == topic.identifier)
+                    .collect();
+                TopicUtilisation {
+                    topic: topic.identifier.clone(),
+                    workshops: held.len(),
+                    participants: held.iter().map(|workshop| workshop.participants).sum(),
+                    has_matching_room: problem
+                        .rooms
+                        .iter()
+                        .any(|room| fulfills_requirements(room, topic)),
+                }
+            })
+            .collect();
+
+        let timeslots = problem
+            .timeslots
+            .iter()
+            .map(|timeslot| TimeslotUtilisation {
+                timeslot: timeslot.identifier.clone(),
+                used_rooms: allocation
+                    .workshops
+                    .iter()
+                    .filter(|workshop| workshop.timeslot == timeslot.identifier)
+                    .count(),
+                rooms: rooms_in_timeslots
+                    .get(timeslot.identifier.as_str())
+                    .map_or(0, Vec::len),
+                participants: allocation
+                    .assignments
+                    .iter()
+                    .filter(|assignment| assignment.timeslot == timeslot.identifier)
+                    .count(),
+                seats: room_sizes
+                    .iter()
+                    .filter(|((room_timeslot, _), _)| *room_timeslot == timeslot.identifier)
+                    .map(|(_, size)| size)
+                    .sum(),
+            })
+            .collect();
+
+        let unassigned = problem
+            .timeslots
+            .iter()
+            .flat_map(|timeslot| {
+                problem
+                    .participants
+                    .iter()
+                    .filter(|participant| participant.is_present(&timeslot.identifier))
+                    .filter(|participant| {
+                        !allocation.assignments.iter().any(|assignment| {
+                            assignment.participant == participant.identifier
+                                && assignment.timeslot == timeslot.identifier
+                        })
+                    })
+                    .map(|participant| UnassignedParticipant {
+                        participant: participant.identifier.clone(),
+                        timeslot: timeslot.identifier.clone(),
+                    })
+            })
+            .collect();
+
+        Self {
+            ranks,
+            workshops,
+            topics,
+            timeslots,
+            unassigned,
+            objective: allocation.objective,
+            gap: allocation.gap,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use crate::{
+        solve, AllocationProblem, AllocationReport, Participant, Preference, Rank, Repetitions,
+        Requirement, Room, RoomAvailability, RoomSize, SolverOptions, Timeslot, Workshop,
+        WorkshopDuration, WorkshopTopic, WorkshopTopicSize,
+    };
+
+    #[test]
+    fn counts_workshop_spanning_timeslots_once() {
+        let problem = AllocationProblem {
+            timeslots: ["morning", "afternoon"]
+                .map(|identifier| Timeslot {
+                    identifier: identifier.to_owned(),
+                })
+                .into(),
+            rooms: vec![Room {
+                identifier: "room".to_owned(),
+                requirements: vec![],
+                max_size: RoomSize(5),
+            }],
+            // the room is smaller in the afternoon
+            room_availability: vec![
+                RoomAvailability {
+                    room: "room".to_owned(),
+                    timeslot: "morning".to_owned(),
+                    max_size: None,
+                },
+                RoomAvailability {
+                    room: "room".to_owned(),
+                    timeslot: "afternoon".to_owned(),
+                    max_size: Some(RoomSize(2)),
+                },
+            ],
+            topics: vec![WorkshopTopic {
+                identifier: "hike".to_owned(),
+                requirements: vec![],
+                max_size: WorkshopTopicSize(10),
+                repetitions: None,
+                duration: WorkshopDuration(2),
+            }],
+            workshops: vec![Workshop {
+                topic: "hike".to_owned(),
+                timeslot: "morning".to_owned(),
+            }],
+            participants: vec![Participant {
+                identifier: "anna".to_owned(),
+                timeslots: None,
+                attributes: BTreeMap::new(),
+            }],
+            preferences: vec![Preference {
+                participant: "anna".to_owned(),
+                topic: "hike".to_owned(),
+                rank: Rank(1),
+            }],
+            ..AllocationProblem::default()
+        };
+        let allocation = solve(&problem, &SolverOptions::default()).unwrap();
+        let report = AllocationReport::new(&problem, &allocation);
+
+        assert_eq!(report.ranks, BTreeMap::from([(Rank(1), 1)]));
+        assert_eq!(report.workshops.len(), 1);
+        assert_eq!(report.workshops[0].timeslot, "morning");
+        assert_eq!(report.workshops[0].participants, 1);
+        assert_eq!(report.workshops[0].capacity, 2);
+        assert!(report
+            .timeslots
+            .iter()
+            .all(|timeslot| timeslot.used_rooms == 1 && timeslot.participants == 1));
+        assert!(report.unassigned.is_empty());
+    }
+
+    #[test]
+    fn lists_optional_topics_that_are_not_held() {
+        let problem = AllocationProblem {
+            requirements: vec![Requirement {
+                identifier: "pool".to_owned(),
+            }],
+            timeslots: vec![Timeslot {
+                identifier: "morning".to_owned(),
+            }],
+            rooms: vec![Room {
+                identifier: "room".to_owned(),
+                requirements: vec![],
+                max_size: RoomSize(5),
+            }],
+            topics: [("hike", vec![]), ("swim", vec!["pool".to_owned()])]
+                .map(|(identifier, requirements)| WorkshopTopic {
+                    identifier: identifier.to_owned(),
+                    requirements,
+                    max_size: WorkshopTopicSize(5),
+                    repetitions: Some(Repetitions { min: 0, max: 1 }),
+                    duration: WorkshopDuration(1),
+                })
+                .into(),
+            participants: vec![Participant {
+                identifier: "anna".to_owned(),
+                timeslots: None,
+                attributes: BTreeMap::new(),
+            }],
+            preferences: ["hike", "swim"]
+                .map(|topic| Preference {
+                    participant: "anna".to_owned(),
+                    topic: topic.to_owned(),
+                    rank: Rank(1),
+                })
+                .into(),
+            ..AllocationProblem::default()
+        };
+        let allocation = solve(&problem, &SolverOptions::default()).unwrap();
+        let report = AllocationReport::new(&problem, &allocation);
+
+        assert_eq!(report.topics.len(), 2);
+        assert_eq!(report.topics[0].workshops, 1);
+        assert_eq!(report.topics[0].participants, 1);
+        assert!(report.topics[0].has_matching_room);
+        assert_eq!(report.topics[1].topic, "swim");
+        assert_eq!(report.topics[1].workshops, 0);
+        assert!(!report.topics[1].has_matching_room);
+    }
+}
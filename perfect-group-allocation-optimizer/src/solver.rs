@@ -0,0 +1,376 @@
+use core::str::FromStr;
+use core::time::Duration;
+use std::time::Instant;
+
+use good_lp::solvers::ObjectiveDirection::Maximisation;
+use good_lp::solvers::{SolutionStatus, WithInitialSolution, WithMipGap, WithTimeLimit};
+use good_lp::{ResolutionError, Solution, SolverModel};
+use tracing::info;
+
+use crate::program::LinearProgram;
+use crate::OptimizerError;
+
+/// The mixed integer linear programming solver used to solve the models.
+///
+/// Besides microlp, which backends are available depends on the enabled cargo features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// COIN-OR Cbc, needs the Cbc system library
+    #[cfg(feature = "coin_cbc")]
+    CoinCbc,
+    /// a pure Rust branch and bound solver, slower than the others
+    /// but it can be built without system libraries
+    MicroLp,
+    /// `HiGHS`, bundled and built from source
+    #[cfg(feature = "highs")]
+    Highs,
+}
+
+impl Backend {
+    /// All backends enabled by cargo features, the first one is the default.
+    pub const AVAILABLE: &'static [Self] = &[
+        #[cfg(feature = "coin_cbc")]
+        Self::CoinCbc,
+        #[cfg(feature = "highs")]
+        Self::Highs,
+        Self::MicroLp,
+    ];
+}
+
+impl FromStr for Backend {
+    type Err = OptimizerError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            #[cfg(feature = "coin_cbc")]
+            "cbc" | "coin_cbc" => Ok(Self::CoinCbc),
+            "microlp" => Ok(Self::MicroLp),
+            #[cfg(feature = "highs")]
+            "highs" => Ok(Self::Highs),
+            _ => Err(OptimizerError::UnknownBackend(name.to_owned())),
+        }
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::AVAILABLE[0]
+    }
+}
+
+/// How the models are solved.
+///
+/// The models are built in a canonical order that doesn't depend on the order of the input,
+/// so the same problem solved with the same options always yields the same allocation,
+/// see [`crate::Allocation::fingerprint`]. This doesn't hold if a `time_limit` is reached.
+///
+/// microlp always uses a single thread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolverOptions {
+    pub backend: Backend,
+    /// seeds the randomized heuristics of the solver which decide between equally good allocations,
+    /// [`SolverOptions::DEFAULT_SEED`] if not specified. It has to be positive, otherwise
+    /// [`OptimizerError::InvalidSeed`], as Cbc needs a positive seed.
+    /// `HiGHS` always uses its own fixed seed and microlp is deterministic.
+    pub seed: u32,
+    /// randomly chooses one of the equally good allocations with this seed
+    /// instead of one that depends on the order of the participants, see [`crate::Allocation::lottery`]
+    pub lottery: Option<u64>,
+    /// the time all solver runs of e.g. [`crate::solve`] may take together, a run that reaches it
+    /// stops with the best solution found so far, see [`crate::Allocation::gap`], and the following
+    /// ones get no time. [`OptimizerError::TimeLimitReached`] if no solution was found.
+    pub time_limit: Option<Duration>,
+    /// stops every solver run once its objective is proven to be within this relative gap
+    /// of the optimum, e.g. `0.01` for one percent
+    pub mip_gap: Option<f64>,
+    /// how many threads the solver may use, by default it decides itself
+    pub threads: Option<u32>,
+    /// runs [`crate::heuristic`] first and passes its allocation to the solver as a starting solution.
+    /// Its summed rank is also added as a lower bound for [`crate::Objective::Utilitarian`]
+    /// and its allocation is returned if the time limit is reached before the solver found one.
+    pub warm_start: bool,
+}
+
+impl SolverOptions {
+    pub const DEFAULT_SEED: u32 = 1;
+}
+
+impl Default for SolverOptions {
+    fn default() -> Self {
+        Self {
+            backend: Backend::default(),
+            seed: Self::DEFAULT_SEED,
+            lottery: None,
+            time_limit: None,
+            mip_gap: None,
+            threads: None,
+            warm_start: false,
+        }
+    }
+}
+
+/// The solver runs of one optimization, e.g. the stages of an [`crate::Objective`],
+/// which share the time limit of their options.
+#[derive(Clone, Copy)]
+pub struct SolverRuns<'o> {
+    pub options: &'o SolverOptions,
+    deadline: Option<Instant>,
+}
+
+impl<'o> SolverRuns<'o> {
+    /// Starts the time limit of `options`.
+    pub fn start(options: &'o SolverOptions) -> Self {
+        Self {
+            options,
+            deadline: options
+                .time_limit
+                .map(|time_limit| Instant::now() + time_limit),
+        }
+    }
+
+    /// The time left for the next run.
+    fn time_limit(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+/// The solution of a [`LinearProgram`].
+pub struct Solved {
+    pub solution: Box<dyn Solution>,
+    /// the value of the reported objective
+    pub objective: f64,
+    /// see [`crate::Allocation::gap`]
+    pub gap: Option<f64>,
+}
+
+/// How far the solved objective `value` may be from the best `bound` proven by the solver,
+/// relative to the value but at least one as the objective values are integral.
+#[cfg(feature = "coin_cbc")]
+fn relative_gap(value: f64, bound: f64) -> f64 {
+    ((bound - value) / value.abs().max(1.0)).max(0.0)
+}
+
+fn solve_using<M: SolverModel<Error = ResolutionError>>(
+    model: M,
+    program: &LinearProgram,
+    gap: impl FnOnce(&M::Solution) -> Option<f64>,
+) -> Result<Solved, OptimizerError>
+where
+    M::Solution: 'static,
+{
+    let solution = program
+        .constraints()
+        .fold(model, SolverModel::with)
+        .solve()?;
+
+    Ok(Solved {
+        objective: solution.eval(&program.objective),
+        gap: gap(&solution),
+        solution: Box::new(solution),
+    })
+}
+
+/// Unlike [`SolverModel::solve`] this returns the best solution found so far
+/// instead of an error if Cbc was stopped by the time limit.
+#[cfg(feature = "coin_cbc")]
+fn solve_cbc(
+    model: good_lp::solvers::coin_cbc::CoinCbcProblem,
+    program: &LinearProgram,
+) -> Result<Solved, OptimizerError> {
+    let mut model = program.constraints().fold(model, SolverModel::with);
+    let solved = model.as_inner_mut().solve();
+    let raw = solved.raw();
+    if raw.is_proven_infeasible() {
+        return Err(ResolutionError::Infeasible.into());
+    }
+    if raw.is_continuous_unbounded() {
+        return Err(ResolutionError::Unbounded.into());
+    }
+    if raw.is_abandoned() {
+        return Err(ResolutionError::Other("Abandoned").into());
+    }
+    // without a solution Cbc returns the values of a relaxation, see `maximise`
+    let solution = program.column_solution(raw.col_solution());
+
+    Ok(Solved {
+        objective: solution.eval(&program.objective),
+        gap: Some(relative_gap(raw.obj_value(), raw.best_possible_value())),
+        solution: Box::new(solution),
+    })
+}
+
+/// Unlike [`SolverModel::solve`] this returns [`OptimizerError::TimeLimitReached`]
+/// if microlp was stopped by the time limit before it found a solution.
+fn solve_microlp(
+    model: good_lp::solvers::microlp::MicroLpProblem,
+    program: &LinearProgram,
+) -> Result<Solved, OptimizerError> {
+    // microlp only reports whether the solution is proven to be optimal
+    solve_using(model, program, |solution| {
+        matches!(solution.status(), SolutionStatus::Optimal).then_some(0.0)
+    })
+    .map_err(|error| match error {
+        // the only error microlp reports this way
+        OptimizerError::Solver(ResolutionError::Other(_)) => OptimizerError::TimeLimitReached,
+        error => error,
+    })
+}
+
+/// Maximizes the objective of `program` within the time left of `runs`.
+pub fn maximise(mut program: LinearProgram, runs: &SolverRuns) -> Result<Solved, OptimizerError> {
+    let options = runs.options;
+    let time_limit = runs.time_limit();
+    if options.seed == 0 {
+        return Err(OptimizerError::InvalidSeed);
+    }
+    if let Some(mip_gap) = options
+        .mip_gap
+        .filter(|mip_gap| mip_gap.is_nan() || *mip_gap < 0.0)
+    {
+        return Err(OptimizerError::InvalidMipGap(mip_gap));
+    }
+    // an earlier run used up the time limit
+    if time_limit == Some(Duration::ZERO) {
+        return Err(OptimizerError::TimeLimitReached);
+    }
+    let variables = program.take_variables();
+    let solved_objective = program.solved_objective();
+    info!("{}", variables.display(&solved_objective));
+
+    let problem = variables.optimise(Maximisation, solved_objective);
+
+    let start = Instant::now();
+    let result = match options.backend {
+        #[cfg(feature = "coin_cbc")]
+        Backend::CoinCbc => {
+            let mut model =
+                good_lp::coin_cbc(problem).with_initial_solution(program.initial_solution.clone());
+            let seed = options.seed.to_string();
+            model.set_parameter("randomSeed", &seed);
+            model.set_parameter("randomCbcSeed", &seed);
+            if let Some(time_limit) = time_limit {
+                model.set_parameter("sec", &time_limit.as_secs_f64().to_string());
+            }
+            if let Some(mip_gap) = options.mip_gap {
+                model.set_parameter("ratioGap", &mip_gap.to_string());
+            }
+            if let Some(threads) = options.threads {
+                model.set_parameter("threads", &threads.to_string());
+            }
+            solve_cbc(model, &program)
+        }
+        Backend::MicroLp => {
+            let mut model =
+                good_lp::microlp(problem).with_initial_solution(program.initial_solution.clone());
+            if let Some(time_limit) = time_limit {
+                model = model.with_time_limit(time_limit.as_secs_f64());
+            }
+            if let Some(mip_gap) = options.mip_gap {
+                #[allow(clippy::cast_possible_truncation)]
+                let relative_gap = mip_gap as f32;
+                model = model
+                    .with_mip_gap(relative_gap)
+                    .map_err(|_| OptimizerError::InvalidMipGap(mip_gap))?;
+            }
+            solve_microlp(model, &program)
+        }
+        #[cfg(feature = "highs")]
+        Backend::Highs => {
+            let mut model =
+                good_lp::highs(problem).with_initial_solution(program.initial_solution.clone());
+            if let Some(time_limit) = time_limit {
+                model = model.set_time_limit(time_limit.as_secs_f64());
+            }
+            if let Some(mip_gap) = options.mip_gap {
+                #[allow(clippy::cast_possible_truncation)]
+                let relative_gap = mip_gap as f32;
+                model = model
+                    .set_mip_rel_gap(relative_gap)
+                    .map_err(|_| OptimizerError::InvalidMipGap(mip_gap))?;
+            }
+            if let Some(threads) = options.threads {
+                model = model.set_threads(threads);
+            }
+            // the solution returned by good_lp doesn't contain the gap
+            solve_using(model, &program, |_| None)
+        }
+    };
+    // the best solution found so far may not be feasible if the time limit was reached
+    let result = result.and_then(|solved| {
+        if time_limit.is_some() && !program.is_feasible(&*solved.solution) {
+            Err(OptimizerError::TimeLimitReached)
+        } else {
+            Ok(solved)
+        }
+    });
+    // to compare the backends
+    info!(
+        "{:?} finished after {:?} with objective and gap {:?}",
+        options.backend,
+        start.elapsed(),
+        result.as_ref().map(|solved| (solved.objective, solved.gap))
+    );
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use good_lp::solvers::WithTimeLimit;
+
+    use super::{maximise, solve_microlp, SolverOptions, SolverRuns};
+    use crate::program::{Domain, LinearProgram};
+    use crate::OptimizerError;
+
+    fn program() -> LinearProgram {
+        let mut program = LinearProgram::default();
+        let chosen = program.add_variable("chosen", Domain::Binary);
+        program.objective = chosen.into();
+        program
+    }
+
+    #[test]
+    fn rejects_zero_seed() {
+        let options = SolverOptions {
+            seed: 0,
+            ..SolverOptions::default()
+        };
+        assert!(matches!(
+            maximise(program(), &SolverRuns::start(&options)),
+            Err(OptimizerError::InvalidSeed)
+        ));
+    }
+
+    #[test]
+    fn shares_time_limit_between_runs() {
+        let options = SolverOptions {
+            time_limit: Some(Duration::from_millis(50)),
+            ..SolverOptions::default()
+        };
+        let runs = SolverRuns::start(&options);
+        let solved = maximise(program(), &runs).unwrap();
+        assert!((solved.objective - 1.0).abs() < f64::EPSILON);
+        // the first run and the sleep use up the time limit
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(runs.time_limit(), Some(Duration::ZERO));
+        assert!(matches!(
+            maximise(program(), &runs),
+            Err(OptimizerError::TimeLimitReached)
+        ));
+    }
+
+    #[test]
+    fn reports_microlp_timeout_as_time_limit_reached() {
+        let mut program = program();
+        let problem = program
+            .take_variables()
+            .maximise(program.solved_objective());
+        let model = good_lp::microlp(problem).with_time_limit(0.0);
+        assert!(matches!(
+            solve_microlp(model, &program),
+            Err(OptimizerError::TimeLimitReached)
+        ));
+    }
+}